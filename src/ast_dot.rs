@@ -0,0 +1,280 @@
+use std::fmt::Write;
+use crate::expression;
+use crate::expression::{Assign, Binary, Call, Expr, Get, Grouping, Literal, Logical, Set, Super, This, Unary, Variable, VisitedElement};
+use crate::statement;
+use crate::statement::{Block, Class, Expression, Function, If, Print, Return, Stmt, Var, While};
+use crate::statement::VisitedElement as VisitedStmt;
+
+/// Draws syntax trees as Graphviz DOT graphs, with one node per expression
+/// or statement and edges labelled after the child they lead to, such as
+/// `left` and `right` for the operands of a binary expression. Statements
+/// are drawn as boxes so they stand out from expressions.
+///
+/// Visiting a node writes it and its children to the graph, and returns its
+/// name for the parent to draw an edge to.
+pub struct AstDot {
+    out: String,
+    nodes: usize,
+}
+
+impl AstDot {
+    pub fn new() -> Self {
+        AstDot {
+            out: String::new(),
+            nodes: 0,
+        }
+    }
+
+    /// The graph of a program, hanging its top-level statements from a
+    /// single `program` node.
+    pub fn graph(&mut self, statements: &[Stmt]) -> String {
+        self.out.clear();
+        self.nodes = 0;
+        self.out.push_str("digraph ast {\n");
+        self.out.push_str("  node [fontname=\"monospace\"];\n");
+        self.out.push_str("  edge [fontname=\"monospace\", fontsize=10];\n");
+        let program = self.node("program", "plaintext");
+        self.statements(&program, statements);
+        self.out.push_str("}\n");
+        self.out.clone()
+    }
+
+    fn node(&mut self, label: &str, shape: &str) -> String {
+        let name = format!("n{}", self.nodes);
+        self.nodes += 1;
+        let _ = writeln!(self.out, "  {name} [label=\"{}\", shape={shape}];", escape(label));
+        name
+    }
+
+    fn expression_node(&mut self, label: &str) -> String {
+        self.node(label, "ellipse")
+    }
+
+    fn statement_node(&mut self, label: &str) -> String {
+        self.node(label, "box")
+    }
+
+    fn edge(&mut self, from: &str, to: &str, label: &str) {
+        let _ = writeln!(self.out, "  {from} -> {to} [label=\"{}\"];", escape(label));
+    }
+
+    fn child(&mut self, parent: &str, label: &str, expression: &Expr) {
+        let child = expression.accept(self);
+        self.edge(parent, &child, label);
+    }
+
+    fn child_statement(&mut self, parent: &str, label: &str, statement: &Stmt) {
+        let child = statement.accept(self);
+        self.edge(parent, &child, label);
+    }
+
+    /// Statements in a sequence, with edges labelled by their position.
+    fn statements(&mut self, parent: &str, statements: &[Stmt]) {
+        for (i, statement) in statements.iter().enumerate() {
+            self.child_statement(parent, &i.to_string(), statement);
+        }
+    }
+}
+
+impl expression::Visitor<String> for AstDot {
+    fn visit_assign(&mut self, element: &Assign) -> String {
+        let node = self.expression_node(&format!("Assign\n{}", element.name.lexeme));
+        self.child(&node, "value", &element.value);
+        node
+    }
+
+    fn visit_binary(&mut self, element: &Binary) -> String {
+        let node = self.expression_node(&format!("Binary\n{}", element.operator.lexeme));
+        self.child(&node, "left", &element.left);
+        self.child(&node, "right", &element.right);
+        node
+    }
+
+    fn visit_call(&mut self, element: &Call) -> String {
+        let node = self.expression_node("Call");
+        self.child(&node, "callee", &element.callee);
+        for (i, argument) in element.arguments.iter().enumerate() {
+            self.child(&node, &format!("argument {i}"), argument);
+        }
+        node
+    }
+
+    fn visit_get(&mut self, element: &Get) -> String {
+        let node = self.expression_node(&format!("Get\n.{}", element.name.lexeme));
+        self.child(&node, "object", &element.object);
+        node
+    }
+
+    fn visit_grouping(&mut self, element: &Grouping) -> String {
+        let node = self.expression_node("Grouping");
+        self.child(&node, "expression", &element.expression);
+        node
+    }
+
+    fn visit_literal(&mut self, element: &Literal) -> String {
+        self.expression_node(&format!("Literal\n{}", element.value))
+    }
+
+    fn visit_logical(&mut self, element: &Logical) -> String {
+        let node = self.expression_node(&format!("Logical\n{}", element.operator.lexeme));
+        self.child(&node, "left", &element.left);
+        self.child(&node, "right", &element.right);
+        node
+    }
+
+    fn visit_set(&mut self, element: &Set) -> String {
+        let node = self.expression_node(&format!("Set\n.{}", element.name.lexeme));
+        self.child(&node, "object", &element.object);
+        self.child(&node, "value", &element.value);
+        node
+    }
+
+    fn visit_super(&mut self, element: &Super) -> String {
+        self.expression_node(&format!("Super\n.{}", element.method.lexeme))
+    }
+
+    fn visit_this(&mut self, _element: &This) -> String {
+        self.expression_node("This")
+    }
+
+    fn visit_unary(&mut self, element: &Unary) -> String {
+        let node = self.expression_node(&format!("Unary\n{}", element.operator.lexeme));
+        self.child(&node, "operand", &element.right);
+        node
+    }
+
+    fn visit_variable(&mut self, element: &Variable) -> String {
+        self.expression_node(&format!("Variable\n{}", element.name.lexeme))
+    }
+}
+
+impl statement::Visitor<String> for AstDot {
+    fn visit_block(&mut self, element: &Block) -> String {
+        let node = self.statement_node("Block");
+        self.statements(&node, &element.statements);
+        node
+    }
+
+    fn visit_class(&mut self, element: &Class) -> String {
+        let node = self.statement_node(&format!("Class\n{}", element.name.lexeme));
+        if let Some(superclass) = &element.superclass {
+            let child = expression::Visitor::visit_variable(self, superclass);
+            self.edge(&node, &child, "superclass");
+        }
+        for method in element.methods.iter() {
+            let child = statement::Visitor::visit_function(self, method);
+            self.edge(&node, &child, "method");
+        }
+        node
+    }
+
+    fn visit_expression(&mut self, element: &Expression) -> String {
+        let node = self.statement_node("Expression");
+        self.child(&node, "expression", &element.expression);
+        node
+    }
+
+    fn visit_function(&mut self, element: &Function) -> String {
+        let params: Vec<&str> = element.params.iter().map(|param| param.lexeme.as_str()).collect();
+        let node = self.statement_node(&format!("Function\n{}({})", element.name.lexeme, params.join(", ")));
+        self.statements(&node, &element.body);
+        node
+    }
+
+    fn visit_if(&mut self, element: &If) -> String {
+        let node = self.statement_node("If");
+        self.child(&node, "condition", &element.condition);
+        self.child_statement(&node, "then", &element.then_branch);
+        if let Some(else_branch) = &element.else_branch {
+            self.child_statement(&node, "else", else_branch);
+        }
+        node
+    }
+
+    fn visit_print(&mut self, element: &Print) -> String {
+        let node = self.statement_node("Print");
+        self.child(&node, "expression", &element.expression);
+        node
+    }
+
+    fn visit_return(&mut self, element: &Return) -> String {
+        let node = self.statement_node("Return");
+        if let Some(value) = &element.value {
+            self.child(&node, "value", value);
+        }
+        node
+    }
+
+    fn visit_var(&mut self, element: &Var) -> String {
+        let node = self.statement_node(&format!("Var\n{}", element.name.lexeme));
+        if let Some(initializer) = &element.initializer {
+            self.child(&node, "initializer", initializer);
+        }
+        node
+    }
+
+    fn visit_while(&mut self, element: &While) -> String {
+        let node = self.statement_node("While");
+        self.child(&node, "condition", &element.condition);
+        self.child_statement(&node, "body", &element.body);
+        node
+    }
+}
+
+/// Escapes text for a quoted DOT string. Newlines become `\n`, which DOT
+/// draws as a centred line break.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse;
+
+    fn graph(source: &str) -> String {
+        AstDot::new().graph(&parse(source))
+    }
+
+    #[test]
+    fn draws_precedence_as_edges() {
+        let dot = graph("print 1 + 2 * -3;");
+        let expected = "digraph ast {
+  node [fontname=\"monospace\"];
+  edge [fontname=\"monospace\", fontsize=10];
+  n0 [label=\"program\", shape=plaintext];
+  n1 [label=\"Print\", shape=box];
+  n2 [label=\"Binary\\n+\", shape=ellipse];
+  n3 [label=\"Literal\\n1\", shape=ellipse];
+  n2 -> n3 [label=\"left\"];
+  n4 [label=\"Binary\\n*\", shape=ellipse];
+  n5 [label=\"Literal\\n2\", shape=ellipse];
+  n4 -> n5 [label=\"left\"];
+  n6 [label=\"Unary\\n-\", shape=ellipse];
+  n7 [label=\"Literal\\n3\", shape=ellipse];
+  n6 -> n7 [label=\"operand\"];
+  n4 -> n6 [label=\"right\"];
+  n2 -> n4 [label=\"right\"];
+  n1 -> n2 [label=\"expression\"];
+  n0 -> n1 [label=\"0\"];
+}
+";
+        assert_eq!(dot, expected);
+    }
+
+    #[test]
+    fn escapes_labels() {
+        let dot = graph(r#"print "a\\b \"hi\"\n";"#);
+        assert!(dot.contains(r#"n2 [label="Literal\n\"a\\b \"hi\"\n\"", shape=ellipse];"#), "{dot}");
+    }
+}
@@ -0,0 +1,435 @@
+use std::rc::Rc;
+use crate::expression;
+use crate::expression::{Assign, Binary, Call, Expr, Get, Grouping, Literal, Logical, Set, Super, This, Unary, Variable, VisitedElement};
+use crate::json::{Json, JsonError};
+use crate::lox_parser::next_id;
+use crate::span::Span;
+use crate::statement;
+use crate::statement::{Block, Class, Expression, Function, If, Print, Return, Stmt, Var, While};
+use crate::statement::VisitedElement as VisitedStmt;
+use crate::token::Token;
+use crate::value::LiteralValue;
+
+/// Serializes syntax trees to JSON, for tools outside of rlox. Every node is
+/// an object with its `kind`, its `span`, and one member per child, named
+/// after the field of the node. Tokens are written as by `Token::to_json`.
+///
+/// The ids the resolver keys variables by are left out: `program_from_json`
+/// hands out fresh ones.
+pub struct AstJson;
+
+impl AstJson {
+    pub fn program(&mut self, statements: &[Stmt]) -> Json {
+        self.statements(statements)
+    }
+
+    fn node(kind: &str, span: Span, mut members: Vec<(&str, Json)>) -> Json {
+        members.insert(0, ("kind", Json::from(kind)));
+        members.push(("span", span.to_json()));
+        Json::object(members)
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) -> Json {
+        Json::Array(statements.iter().map(|statement| statement.accept(self)).collect())
+    }
+
+    fn optional_expression(&mut self, expression: &Option<Box<Expr>>) -> Json {
+        match expression {
+            Some(expression) => expression.accept(self),
+            None => Json::Null,
+        }
+    }
+}
+
+impl expression::Visitor<Json> for AstJson {
+    fn visit_assign(&mut self, element: &Assign) -> Json {
+        AstJson::node("Assign", element.span, vec![
+            ("name", element.name.to_json()),
+            ("value", element.value.accept(self)),
+        ])
+    }
+
+    fn visit_binary(&mut self, element: &Binary) -> Json {
+        AstJson::node("Binary", element.span, vec![
+            ("left", element.left.accept(self)),
+            ("operator", element.operator.to_json()),
+            ("right", element.right.accept(self)),
+        ])
+    }
+
+    fn visit_call(&mut self, element: &Call) -> Json {
+        let arguments = element.arguments.iter().map(|argument| argument.accept(self)).collect();
+        AstJson::node("Call", element.span, vec![
+            ("callee", element.callee.accept(self)),
+            ("paren", element.paren.to_json()),
+            ("arguments", Json::Array(arguments)),
+        ])
+    }
+
+    fn visit_get(&mut self, element: &Get) -> Json {
+        AstJson::node("Get", element.span, vec![
+            ("object", element.object.accept(self)),
+            ("name", element.name.to_json()),
+        ])
+    }
+
+    fn visit_grouping(&mut self, element: &Grouping) -> Json {
+        AstJson::node("Grouping", element.span, vec![
+            ("expression", element.expression.accept(self)),
+        ])
+    }
+
+    fn visit_literal(&mut self, element: &Literal) -> Json {
+        AstJson::node("Literal", element.span, literal_to_json(&element.value))
+    }
+
+    fn visit_logical(&mut self, element: &Logical) -> Json {
+        AstJson::node("Logical", element.span, vec![
+            ("left", element.left.accept(self)),
+            ("operator", element.operator.to_json()),
+            ("right", element.right.accept(self)),
+        ])
+    }
+
+    fn visit_set(&mut self, element: &Set) -> Json {
+        AstJson::node("Set", element.span, vec![
+            ("object", element.object.accept(self)),
+            ("name", element.name.to_json()),
+            ("value", element.value.accept(self)),
+        ])
+    }
+
+    fn visit_super(&mut self, element: &Super) -> Json {
+        AstJson::node("Super", element.span, vec![
+            ("keyword", element.keyword.to_json()),
+            ("method", element.method.to_json()),
+        ])
+    }
+
+    fn visit_this(&mut self, element: &This) -> Json {
+        AstJson::node("This", element.span, vec![
+            ("keyword", element.keyword.to_json()),
+        ])
+    }
+
+    fn visit_unary(&mut self, element: &Unary) -> Json {
+        AstJson::node("Unary", element.span, vec![
+            ("operator", element.operator.to_json()),
+            ("right", element.right.accept(self)),
+        ])
+    }
+
+    fn visit_variable(&mut self, element: &Variable) -> Json {
+        AstJson::node("Variable", element.span, vec![
+            ("name", element.name.to_json()),
+        ])
+    }
+}
+
+impl statement::Visitor<Json> for AstJson {
+    fn visit_block(&mut self, element: &Block) -> Json {
+        AstJson::node("Block", element.span, vec![
+            ("statements", self.statements(&element.statements)),
+        ])
+    }
+
+    fn visit_class(&mut self, element: &Class) -> Json {
+        let superclass = match &element.superclass {
+            Some(superclass) => expression::Visitor::visit_variable(self, superclass),
+            None => Json::Null,
+        };
+        let methods = element.methods.iter().map(|method| self.visit_function(method)).collect();
+        AstJson::node("Class", element.span, vec![
+            ("name", element.name.to_json()),
+            ("superclass", superclass),
+            ("methods", Json::Array(methods)),
+        ])
+    }
+
+    fn visit_expression(&mut self, element: &Expression) -> Json {
+        AstJson::node("Expression", element.span, vec![
+            ("expression", element.expression.accept(self)),
+        ])
+    }
+
+    fn visit_function(&mut self, element: &Function) -> Json {
+        let params = element.params.iter().map(Token::to_json).collect();
+        AstJson::node("Function", element.span, vec![
+            ("name", element.name.to_json()),
+            ("params", Json::Array(params)),
+            ("body", self.statements(&element.body)),
+        ])
+    }
+
+    fn visit_if(&mut self, element: &If) -> Json {
+        let else_branch = match &element.else_branch {
+            Some(else_branch) => else_branch.accept(self),
+            None => Json::Null,
+        };
+        AstJson::node("If", element.span, vec![
+            ("condition", element.condition.accept(self)),
+            ("then_branch", element.then_branch.accept(self)),
+            ("else_branch", else_branch),
+        ])
+    }
+
+    fn visit_print(&mut self, element: &Print) -> Json {
+        AstJson::node("Print", element.span, vec![
+            ("expression", element.expression.accept(self)),
+        ])
+    }
+
+    fn visit_return(&mut self, element: &Return) -> Json {
+        AstJson::node("Return", element.span, vec![
+            ("keyword", element.keyword.to_json()),
+            ("value", self.optional_expression(&element.value)),
+        ])
+    }
+
+    fn visit_var(&mut self, element: &Var) -> Json {
+        AstJson::node("Var", element.span, vec![
+            ("name", element.name.to_json()),
+            ("initializer", self.optional_expression(&element.initializer)),
+        ])
+    }
+
+    fn visit_while(&mut self, element: &While) -> Json {
+        AstJson::node("While", element.span, vec![
+            ("condition", element.condition.accept(self)),
+            ("body", element.body.accept(self)),
+        ])
+    }
+}
+
+/// Literals are tagged with their type, so that numbers JSON can't
+/// represent, such as infinity, can be written as strings.
+fn literal_to_json(literal: &LiteralValue) -> Vec<(&'static str, Json)> {
+    let (literal_type, value) = match literal {
+        LiteralValue::Nil => ("nil", Json::Null),
+        LiteralValue::Bool(b) => ("bool", Json::Bool(*b)),
+        LiteralValue::Number(n) if n.is_finite() => ("number", Json::Number(*n)),
+        LiteralValue::Number(n) => ("number", Json::String(n.to_string())),
+        LiteralValue::Str(s) => ("string", Json::from(s.as_str())),
+    };
+    vec![("type", Json::from(literal_type)), ("value", value)]
+}
+
+fn literal_from_json(json: &Json) -> Result<LiteralValue, JsonError> {
+    let value = json.field("value")?;
+    let literal = match json.field("type")?.as_str()? {
+        "nil" => LiteralValue::Nil,
+        "bool" => LiteralValue::Bool(value.as_bool()?),
+        "number" => match value {
+            Json::String(n) => LiteralValue::Number(n.parse::<f64>()
+                .map_err(|_| JsonError::new(&format!("Invalid number '{n}'.")))?),
+            value => LiteralValue::Number(value.as_f64()?),
+        },
+        "string" => LiteralValue::Str(value.as_str()?.to_string()),
+        other => return Err(JsonError::new(&format!("Unknown literal type '{other}'."))),
+    };
+    Ok(literal)
+}
+
+/// Rebuilds a program from the output of `AstJson::program`.
+pub fn program_from_json(json: &Json) -> Result<Vec<Stmt>, JsonError> {
+    statements_from_json(json)
+}
+
+fn statements_from_json(json: &Json) -> Result<Vec<Stmt>, JsonError> {
+    json.as_array()?.iter().map(stmt_from_json).collect()
+}
+
+fn stmt_from_json(json: &Json) -> Result<Stmt, JsonError> {
+    let kind = json.field("kind")?.as_str()?;
+    let span = Span::from_json(json.field("span")?)?;
+    let statement = match kind {
+        "Block" => Stmt::Block(Block::new(statements_from_json(json.field("statements")?)?, span)),
+        "Class" => {
+            let superclass = match json.field("superclass")? {
+                Json::Null => None,
+                superclass => Some(variable_from_json(superclass)?),
+            };
+            let methods = json.field("methods")?.as_array()?.iter()
+                .map(function_from_json)
+                .collect::<Result<Vec<Function>, JsonError>>()?;
+            Stmt::Class(Class::new(token(json, "name")?, superclass, methods, span))
+        }
+        "Expression" => Stmt::Expression(Expression::new(child(json, "expression")?, span)),
+        "Function" => Stmt::Function(function_from_json(json)?),
+        "If" => {
+            let else_branch = match json.field("else_branch")? {
+                Json::Null => None,
+                else_branch => Some(Box::new(stmt_from_json(else_branch)?)),
+            };
+            let then_branch = Box::new(stmt_from_json(json.field("then_branch")?)?);
+            Stmt::If(If::new(child(json, "condition")?, then_branch, else_branch, span))
+        }
+        "Print" => Stmt::Print(Print::new(child(json, "expression")?, span)),
+        "Return" => Stmt::Return(Return::new(token(json, "keyword")?, optional_child(json, "value")?, span)),
+        "Var" => Stmt::Var(Var::new(token(json, "name")?, optional_child(json, "initializer")?, span)),
+        "While" => {
+            let body = Box::new(stmt_from_json(json.field("body")?)?);
+            Stmt::While(While::new(child(json, "condition")?, body, span))
+        }
+        other => return Err(JsonError::new(&format!("Unknown statement kind '{other}'."))),
+    };
+    Ok(statement)
+}
+
+fn function_from_json(json: &Json) -> Result<Function, JsonError> {
+    let span = Span::from_json(json.field("span")?)?;
+    let params = json.field("params")?.as_array()?.iter()
+        .map(Token::from_json)
+        .collect::<Result<Vec<Token>, JsonError>>()?;
+    let body = statements_from_json(json.field("body")?)?;
+    Ok(Function::new(token(json, "name")?, params, Rc::new(body), span))
+}
+
+fn variable_from_json(json: &Json) -> Result<Variable, JsonError> {
+    let span = Span::from_json(json.field("span")?)?;
+    Ok(Variable::new(token(json, "name")?, next_id(), span))
+}
+
+fn expr_from_json(json: &Json) -> Result<Expr, JsonError> {
+    let kind = json.field("kind")?.as_str()?;
+    let span = Span::from_json(json.field("span")?)?;
+    let expression = match kind {
+        "Assign" => Expr::Assign(Assign::new(token(json, "name")?, child(json, "value")?, next_id(), span)),
+        "Binary" => Expr::Binary(Binary::new(child(json, "left")?, token(json, "operator")?, child(json, "right")?, span)),
+        "Call" => {
+            let arguments = json.field("arguments")?.as_array()?.iter()
+                .map(expr_from_json)
+                .collect::<Result<Vec<Expr>, JsonError>>()?;
+            Expr::Call(Call::new(child(json, "callee")?, token(json, "paren")?, arguments, span))
+        }
+        "Get" => Expr::Get(Get::new(child(json, "object")?, token(json, "name")?, span)),
+        "Grouping" => Expr::Grouping(Grouping::new(child(json, "expression")?, span)),
+        "Literal" => Expr::Literal(Literal::new(literal_from_json(json)?, span)),
+        "Logical" => Expr::Logical(Logical::new(child(json, "left")?, token(json, "operator")?, child(json, "right")?, span)),
+        "Set" => Expr::Set(Set::new(child(json, "object")?, token(json, "name")?, child(json, "value")?, span)),
+        "Super" => Expr::Super(Super::new(token(json, "keyword")?, token(json, "method")?, next_id(), span)),
+        "This" => Expr::This(This::new(token(json, "keyword")?, next_id(), span)),
+        "Unary" => Expr::Unary(Unary::new(token(json, "operator")?, child(json, "right")?, span)),
+        "Variable" => Expr::Variable(variable_from_json(json)?),
+        other => return Err(JsonError::new(&format!("Unknown expression kind '{other}'."))),
+    };
+    Ok(expression)
+}
+
+fn token(json: &Json, key: &str) -> Result<Token, JsonError> {
+    Token::from_json(json.field(key)?)
+}
+
+fn child(json: &Json, key: &str) -> Result<Box<Expr>, JsonError> {
+    Ok(Box::new(expr_from_json(json.field(key)?)?))
+}
+
+fn optional_child(json: &Json, key: &str) -> Result<Option<Box<Expr>>, JsonError> {
+    match json.field(key)? {
+        Json::Null => Ok(None),
+        child => Ok(Some(Box::new(expr_from_json(child)?))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_printer::AstPrinter;
+    use crate::test_support::parse;
+
+    /// Serializes the program, reads it back from the JSON text, and checks
+    /// that serializing the result gives the same JSON again.
+    fn assert_round_trip(source: &str) {
+        let statements = parse(source);
+        let json = AstJson.program(&statements).to_string();
+
+        let parsed = Json::parse(&json).unwrap_or_else(|error| panic!("invalid JSON for {source:?}: {error}"));
+        let rebuilt = program_from_json(&parsed).unwrap_or_else(|error| panic!("can't rebuild {source:?}: {error}"));
+
+        assert_eq!(AstJson.program(&rebuilt).to_string(), json, "round trip of {source:?}");
+        assert_eq!(AstPrinter::new().print(&rebuilt), AstPrinter::new().print(&statements), "round trip of {source:?}");
+    }
+
+    #[test]
+    fn round_trips_expressions() {
+        let cases = [
+            "1 + 2 * 3 - 4 / 5;",
+            "-(1) == !true;",
+            "a = b = c;",
+            "x < y and y <= z or x > z and z >= y or x != y;",
+            "f(1, g(2), \"three\")(4);",
+            "point.x = point.y.z;",
+            "nil; true; false;",
+        ];
+        for source in cases {
+            assert_round_trip(source);
+        }
+    }
+
+    #[test]
+    fn round_trips_statements() {
+        let cases = [
+            "var a; var b = 1; print a;",
+            "{ var a = 1; { print a; } }",
+            "if (a) print 1; if (b) print 2; else { print 3; }",
+            "while (i < 10) i = i + 1;",
+            "for (var i = 0; i < 3; i = i + 1) print i;",
+            "fun add(a, b) { return a + b; } fun nothing() { return; }",
+            "class A { init(x) { this.x = x; } } class B < A { get() { return super.get(); } }",
+        ];
+        for source in cases {
+            assert_round_trip(source);
+        }
+    }
+
+    #[test]
+    fn round_trips_literals() {
+        let cases = [
+            "print \"quote \\\" backslash \\\\ tab \\t newline \\n\";",
+            "print \"unicode \\u{1F600} é\";",
+            "print 0.1; print 1e300; print 1e999; print 0xFF;",
+            "print \"multi\nline\";",
+        ];
+        for source in cases {
+            assert_round_trip(source);
+        }
+    }
+
+    #[test]
+    fn keeps_literal_values_and_spans() {
+        let statements = parse("print \"1\" + 1;");
+        let json = Json::parse(&AstJson.program(&statements).to_string()).unwrap();
+        let rebuilt = program_from_json(&json).unwrap();
+
+        let Stmt::Print(print) = &rebuilt[0] else {
+            panic!("expected a print statement");
+        };
+        let Expr::Binary(binary) = print.expression.as_ref() else {
+            panic!("expected a binary expression");
+        };
+        let (Expr::Literal(left), Expr::Literal(right)) = (binary.left.as_ref(), binary.right.as_ref()) else {
+            panic!("expected literal operands");
+        };
+        assert_eq!(left.value, LiteralValue::Str("1".to_string()));
+        assert_eq!(right.value, LiteralValue::Number(1.0));
+        assert_eq!(binary.operator.lexeme, "+");
+        assert_eq!(binary.span, Span::new(6, 13, 1, 7));
+        assert_eq!(print.span, Span::new(0, 14, 1, 1));
+    }
+
+    #[test]
+    fn rejects_malformed_trees() {
+        let cases = [
+            (r#"[{"kind":"Bogus","span":{"start":0,"end":0,"line":1,"column":1}}]"#, "Unknown statement kind 'Bogus'."),
+            ("{}", "Expect an array, got {}."),
+            ("[{\"kind\":\"Print\"}]", "Missing member 'span'."),
+        ];
+        for (text, message) in cases {
+            let json = Json::parse(text).unwrap();
+            let error = program_from_json(&json).err().expect("malformed tree should be rejected");
+            assert_eq!(error.message, message);
+        }
+        assert!(Json::parse("[1,]").is_err());
+        assert!(Json::parse("{\"a\": 1} x").is_err());
+    }
+}
@@ -1,6 +1,6 @@
-use std::io::BufWriter;
-use std::ops::Add;
 use crate::expression::*;
+use crate::statement;
+use crate::statement::{Stmt, VisitedElement as VisitedStmt};
 
 #[derive(Clone, Copy)]
 pub struct AstPrinter {
@@ -14,46 +14,140 @@ impl AstPrinter {
         }
     }
 
-    fn parenthesize(self, name: String, exprs: Vec<&Expr>) -> String {
+    /// Prints a program, one top-level statement per line.
+    pub fn print(&mut self, statements: &[Stmt]) -> String {
+        statements.iter()
+            .map(|statement| format!("{}\n", statement.accept(self)))
+            .collect()
+    }
+
+    /// Prints statements separated by spaces, as nested in a block or body.
+    fn print_all(&mut self, statements: &[Stmt]) -> String {
+        statements.iter()
+            .map(|statement| format!(" {}", statement.accept(self)))
+            .collect()
+    }
+
+    fn parenthesize(&mut self, name: String, exprs: Vec<&Expr>) -> String {
         let mut builder = String::new();
         builder.push_str(format!("({name}").as_str());
         for x in exprs {
-            builder.push_str(" ");
-            let res = match x {
-                Expr::Binary(e) => {
-                    e.accept(self)
-                }
-                Expr::Grouping(e) => {
-                    e.accept(self)
-                }
-                Expr::Literal(e) => {
-                    e.accept(self)
-                }
-                Expr::Unary(e) => {
-                    e.accept(self)
-                }
-            };
+            builder.push(' ');
+            let res = x.accept(self);
             builder.push_str(res.as_str());
         }
-        builder.push_str(")");
-        return builder;
+        builder.push(')');
+        builder
     }
 }
 
 impl Visitor<String> for AstPrinter {
-    fn visit_binary(&self, element: &Binary) -> String {
+    fn visit_assign(&mut self, element: &Assign) -> String {
+        self.parenthesize(format!("= {}", element.name.lexeme), vec![element.value.as_ref()])
+    }
+
+    fn visit_binary(&mut self, element: &Binary) -> String {
         self.parenthesize(element.operator.lexeme.clone(), vec![element.left.as_ref(), element.right.as_ref()])
     }
 
-    fn visit_grouping(&self, element: &Grouping) -> String {
+    fn visit_call(&mut self, element: &Call) -> String {
+        let mut exprs = vec![element.callee.as_ref()];
+        exprs.extend(element.arguments.iter());
+        self.parenthesize("call".to_string(), exprs)
+    }
+
+    fn visit_get(&mut self, element: &Get) -> String {
+        self.parenthesize(format!(". {}", element.name.lexeme), vec![element.object.as_ref()])
+    }
+
+    fn visit_grouping(&mut self, element: &Grouping) -> String {
         self.parenthesize("Group".to_string(), vec![element.expression.as_ref()])
     }
 
-    fn visit_literal(&self, element: &Literal) -> String {
-        element.value.clone().unwrap_or_else(|| "nil".to_string())
+    fn visit_literal(&mut self, element: &Literal) -> String {
+        element.value.to_string()
     }
 
-    fn visit_unary(&self, element: &Unary) -> String {
+    fn visit_logical(&mut self, element: &Logical) -> String {
+        self.parenthesize(element.operator.lexeme.clone(), vec![element.left.as_ref(), element.right.as_ref()])
+    }
+
+    fn visit_set(&mut self, element: &Set) -> String {
+        self.parenthesize(format!("= . {}", element.name.lexeme), vec![element.object.as_ref(), element.value.as_ref()])
+    }
+
+    fn visit_super(&mut self, element: &Super) -> String {
+        format!("(super {})", element.method.lexeme)
+    }
+
+    fn visit_this(&mut self, _element: &This) -> String {
+        "this".to_string()
+    }
+
+    fn visit_unary(&mut self, element: &Unary) -> String {
         self.parenthesize(element.operator.lexeme.clone(), vec![element.right.as_ref()])
     }
-}
\ No newline at end of file
+
+    fn visit_variable(&mut self, element: &Variable) -> String {
+        element.name.lexeme.clone()
+    }
+}
+
+impl statement::Visitor<String> for AstPrinter {
+    fn visit_block(&mut self, element: &statement::Block) -> String {
+        format!("(block{})", self.print_all(&element.statements))
+    }
+
+    fn visit_class(&mut self, element: &statement::Class) -> String {
+        let mut builder = format!("(class {}", element.name.lexeme);
+        if let Some(superclass) = &element.superclass {
+            builder.push_str(format!(" < {}", superclass.name.lexeme).as_str());
+        }
+        for method in element.methods.iter() {
+            builder.push(' ');
+            builder.push_str(self.visit_function(method).as_str());
+        }
+        builder.push(')');
+        builder
+    }
+
+    fn visit_expression(&mut self, element: &statement::Expression) -> String {
+        self.parenthesize(";".to_string(), vec![element.expression.as_ref()])
+    }
+
+    fn visit_function(&mut self, element: &statement::Function) -> String {
+        let params: Vec<&str> = element.params.iter().map(|param| param.lexeme.as_str()).collect();
+        format!("(fun {}({}){})", element.name.lexeme, params.join(" "), self.print_all(&element.body))
+    }
+
+    fn visit_if(&mut self, element: &statement::If) -> String {
+        let condition = element.condition.accept(self);
+        let then_branch = element.then_branch.accept(self);
+        match &element.else_branch {
+            Some(else_branch) => format!("(if-else {condition} {then_branch} {})", else_branch.accept(self)),
+            None => format!("(if {condition} {then_branch})"),
+        }
+    }
+
+    fn visit_print(&mut self, element: &statement::Print) -> String {
+        self.parenthesize("print".to_string(), vec![element.expression.as_ref()])
+    }
+
+    fn visit_return(&mut self, element: &statement::Return) -> String {
+        match &element.value {
+            Some(value) => self.parenthesize("return".to_string(), vec![value.as_ref()]),
+            None => "(return)".to_string(),
+        }
+    }
+
+    fn visit_var(&mut self, element: &statement::Var) -> String {
+        match &element.initializer {
+            Some(initializer) => self.parenthesize(format!("var {} =", element.name.lexeme), vec![initializer.as_ref()]),
+            None => format!("(var {})", element.name.lexeme),
+        }
+    }
+
+    fn visit_while(&mut self, element: &statement::While) -> String {
+        format!("(while {} {})", element.condition.accept(self), element.body.accept(self))
+    }
+}
@@ -1,51 +1,68 @@
 use std::fs::File;
-use std::hash::Hash;
 use std::io::Write;
 fn main() {
     let mut buffer = File::create("src/expression.rs").unwrap();
-    define_ast(vec![
+    define_ast("Expr", vec![
+        "Assign : Token name, Expr value, usize id",
         "Binary : Expr left, Token operator, Expr right",
+        "Call : Expr callee, Token paren, List<Expr> arguments",
+        "Get : Expr object, Token name",
         "Grouping : Expr expression",
-        "Literal : String value",
+        "Literal : LiteralValue value",
+        "Logical : Expr left, Token operator, Expr right",
+        "Set : Expr object, Token name, Expr value",
+        "Super : Token keyword, Token method, usize id",
+        "This : Token keyword, usize id",
         "Unary : Token operator, Expr right",
-    ], &mut buffer);
+        "Variable : Token name, usize id",
+    ], vec!["crate::span::Span", "crate::token::Token", "crate::value::LiteralValue"], &mut buffer);
+
+    let mut buffer = File::create("src/statement.rs").unwrap();
+    define_ast("Stmt", vec![
+        "Block : List<Stmt> statements",
+        "Class : Token name, Variable? superclass, List<Function> methods",
+        "Expression : Expr expression",
+        "Function : Token name, List<Token> params, Rc<Vec<Stmt>> body",
+        "If : Expr condition, Stmt then_branch, Stmt? else_branch",
+        "Print : Expr expression",
+        "Return : Token keyword, Expr? value",
+        "Var : Token name, Expr? initializer",
+        "While : Expr condition, Stmt body",
+    ], vec!["std::rc::Rc", "crate::expression::{Expr, Variable}", "crate::span::Span", "crate::token::Token"], &mut buffer);
 }
 
-fn define_ast(types: Vec<&str>, buffer: &mut File) {
-    // header and enum for Expr
-    buffer.write_all("use std::any::Any;\nuse crate::token::Token;\n".as_bytes()).unwrap();
-    buffer.write_all("pub enum Expr {\n".as_bytes()).unwrap();
+fn define_ast(base_name: &str, types: Vec<&str>, imports: Vec<&str>, buffer: &mut File) {
+    // header and enum for the base type
+    for import in imports {
+        buffer.write_all(format!("use {import};\n").as_bytes()).unwrap();
+    }
+    buffer.write_all(format!("#[derive(Clone)]\npub enum {base_name} {{\n").as_bytes()).unwrap();
     for x in types.clone() {
         let (rule, _) = x.split_once(" : ").unwrap();
         buffer.write_all(format!("\t{rule}({rule}),\n").as_bytes()).unwrap();
     }
     buffer.write_all("}\n\n".as_bytes()).unwrap();
 
-    define_visitor(types.clone(), "Expr".to_string(), buffer);
+    define_visitor(types.clone(), buffer);
 
     // all structs for rules
     for x in types.clone() {
-        let (rule, producer) = x.split_once(" : ").unwrap();
-        let rule_struct_start = format!("pub struct {rule} {{\n");
+        let (rule, fields) = x.split_once(" : ").unwrap();
+        // every node records the source it was parsed from
+        let producer = format!("{fields}, Span span");
+        let rule_struct_start = format!("#[derive(Clone)]\npub struct {rule} {{\n");
         buffer.write_all(rule_struct_start.as_bytes()).unwrap();
 
         for x in producer.split(", ") {
             let (struct_fieldtype, struct_field) = x.split_once(" ").unwrap();
 
-            let wrapped_type = if struct_fieldtype == "Expr" {
-                format!("Box<{struct_fieldtype}>")
-            } else if struct_fieldtype == "String" {
-                format!("Option<{struct_fieldtype}>")
-                // format!("Box<dyn {struct_fieldtype}>")
-            } else {
-                struct_fieldtype.to_string()
-            };
+            let wrapped_type = wrap_type(struct_fieldtype);
 
             let field = format!("\tpub {struct_field}: {wrapped_type},\n");
             buffer.write_all(field.as_bytes()).unwrap();
         }
 
-        let rule_struct_end = format!("}}\n\n");
+        let rule_struct_end = "}\n\n";
         buffer.write_all(rule_struct_end.as_bytes()).unwrap();
 
         // all impl of constructor for rules
@@ -54,14 +71,7 @@ fn define_ast(types: Vec<&str>, buffer: &mut File) {
         buffer.write_all("\tpub fn new(".as_bytes()).unwrap();
         for x in producer.split(", ") {
             let (struct_fieldtype, struct_field) = x.split_once(" ").unwrap();
-            let wrapped_type = if struct_fieldtype == "Expr" {
-                format!("Box<{struct_fieldtype}>")
-            } else if struct_fieldtype == "String" {
-                format!("Option<{struct_fieldtype}>")
-                // format!("Box<dyn {struct_fieldtype}>")
-            } else {
-                struct_fieldtype.to_string()
-            };
+            let wrapped_type = wrap_type(struct_fieldtype);
             let field = format!("{struct_field}: {wrapped_type}, ");
             buffer.write_all(field.as_bytes()).unwrap();
         }
@@ -80,39 +90,64 @@ fn define_ast(types: Vec<&str>, buffer: &mut File) {
         // implement VisitedElement for rule
         let impl_start = format!("impl VisitedElement for {} {{\n", rule);
         buffer.write_all(impl_start.as_bytes()).unwrap();
-        buffer.write_all("\tfn accept<V: Visitor<R>, R>(&self, visitor: V) -> R {\n".as_bytes()).unwrap();
+        buffer.write_all("\tfn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {\n".as_bytes()).unwrap();
         let rule_lower = rule.to_lowercase();
-        buffer.write_all(format!("\t\tvisitor.visit_{rule_lower}(&self)\n").as_bytes()).unwrap();
+        buffer.write_all(format!("\t\tvisitor.visit_{rule_lower}(self)\n").as_bytes()).unwrap();
         buffer.write_all("\t}\n".as_bytes()).unwrap();
         buffer.write_all("}\n\n".as_bytes()).unwrap();
         // end VisitedElement
 
     }
 
-    // VisitedElement for Expr
-
+    // VisitedElement for the base type
+    buffer.write_all(format!("impl VisitedElement for {base_name} {{\n").as_bytes()).unwrap();
+    buffer.write_all("\tfn accept<S: Visitor<R>, R>(&self, visitor: &mut S) -> R {\n".as_bytes()).unwrap();
+    buffer.write_all("\t\tmatch self {\n".as_bytes()).unwrap();
+    for x in types.clone() {
+        let (rule, _) = x.split_once(" : ").unwrap();
+        buffer.write_all(format!("\t\t\t {base_name}::{rule}(b) => {{b.accept(visitor)}},\n").as_bytes()).unwrap();
+    }
+    buffer.write_all("\t\t}\n".as_bytes()).unwrap();
+    buffer.write_all("\t}\n".as_bytes()).unwrap();
+    buffer.write_all("}\n\n".as_bytes()).unwrap();
 
-    buffer.write_all("impl VisitedElement for Expr {\n".as_bytes()).unwrap();
-    buffer.write_all("\tfn accept<S: Visitor<R>, R>(&self, visitor: S) -> R {\n".as_bytes()).unwrap();
+    // span accessor for the base type
+    buffer.write_all(format!("impl {base_name} {{\n").as_bytes()).unwrap();
+    buffer.write_all("\t#[allow(dead_code)]\n".as_bytes()).unwrap();
+    buffer.write_all("\tpub fn span(&self) -> Span {\n".as_bytes()).unwrap();
     buffer.write_all("\t\tmatch self {\n".as_bytes()).unwrap();
     for x in types {
         let (rule, _) = x.split_once(" : ").unwrap();
-        buffer.write_all(format!("\t\t\t Expr::{rule}(b) => {{b.accept(visitor)}},\n").as_bytes()).unwrap();
+        buffer.write_all(format!("\t\t\t {base_name}::{rule}(b) => {{b.span}},\n").as_bytes()).unwrap();
     }
     buffer.write_all("\t\t}\n".as_bytes()).unwrap();
     buffer.write_all("\t}\n".as_bytes()).unwrap();
     buffer.write_all("}\n".as_bytes()).unwrap();
 }
 
-fn define_visitor(types: Vec<&str>, base_name: String, buffer: &mut File) {
+fn define_visitor(types: Vec<&str>, buffer: &mut File) {
     buffer.write_all(b"pub trait Visitor<R> {\n").unwrap();
     for x in types {
         let (rule, _) = x.split_once(" : ").unwrap();
         let rule_lower = rule.to_lowercase();
-        buffer.write_all(format!("\tfn visit_{rule_lower}(&self, element: &{rule}) -> R ;\n").as_bytes()).unwrap();
+        buffer.write_all(format!("\tfn visit_{rule_lower}(&mut self, element: &{rule}) -> R ;\n").as_bytes()).unwrap();
     }
     buffer.write_all(b"}\n\n").unwrap();
     buffer.write_all(b"pub trait VisitedElement {\n").unwrap();
-    buffer.write_all(b"\tfn accept<S: Visitor<R>, R>(&self, visitor: S) -> R;\n").unwrap();
+    buffer.write_all(b"\tfn accept<S: Visitor<R>, R>(&self, visitor: &mut S) -> R;\n").unwrap();
     buffer.write_all(b"}\n\n").unwrap();
-}
\ No newline at end of file
+}
+
+/// Maps a type from the grammar description to the Rust field type.
+/// Nested nodes are boxed, and a trailing `?` marks an optional field.
+fn wrap_type(field_type: &str) -> String {
+    if let Some(inner) = field_type.strip_suffix('?') {
+        format!("Option<{}>", wrap_type(inner))
+    } else if let Some(inner) = field_type.strip_prefix("List<").and_then(|t| t.strip_suffix('>')) {
+        format!("Vec<{inner}>")
+    } else if field_type == "Expr" || field_type == "Stmt" {
+        format!("Box<{field_type}>")
+    } else {
+        field_type.to_string()
+    }
+}
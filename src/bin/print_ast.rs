@@ -1,18 +0,0 @@
-// FIXME: unable to import from src
-
-fn main() {
-//     let expr = Expr::Binary(Binary::new(
-//         Box::new(Expr::Unary(Unary::new(
-//             Token::new(TokenType::Minus, "-".to_string(), "".to_string(), 1),
-//             Box::new(Expr::Literal(Literal::new(Some("123".to_string())))),
-//         ))),
-//         Token::new(TokenType::Star, "*".to_string(), "".to_string(), 1),
-//         Box::new(Expr::Grouping(Grouping::new(
-//             Box::new(Expr::Literal(Literal::new(Some("45.67".to_string()))))
-//         )))
-//     ));
-//     let printer: AstPrinter = AstPrinter { };
-//     let pretty = expr.accept(printer);
-//
-//     println!("{pretty}");
-}
\ No newline at end of file
@@ -0,0 +1,80 @@
+use std::fmt;
+use crate::span::Span;
+
+/// Error codes, one for each phase that can reject a program.
+pub const SCAN_ERROR: &str = "E0001";
+pub const SYNTAX_ERROR: &str = "E0002";
+pub const RESOLUTION_ERROR: &str = "E0003";
+pub const RUNTIME_ERROR: &str = "E0004";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    #[allow(dead_code)] // nothing reports warnings yet
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A secondary location shown alongside a diagnostic, such as the
+/// declaration a redeclared variable clashes with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem found in a Lox program, by any phase from the scanner to the
+/// interpreter. Phases collect these instead of printing them, and leave it
+/// to the caller to decide how to render them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    /// A suggestion on how to fix the problem.
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: &str, span: Span) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code,
+            message: message.to_string(),
+            span,
+            labels: vec![],
+            notes: vec![],
+            help: None,
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push(Label { span, message: message.to_string() });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
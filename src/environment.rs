@@ -0,0 +1,125 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::interpreter::RuntimeError;
+use crate::token::Token;
+use crate::value::Value;
+
+/// Storage for the variables of one Lox scope, bound by name. Scopes are
+/// chained through `enclosing` up to the global one.
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment {
+            ..Default::default()
+        }
+    }
+
+    pub fn new_enclosed(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Environment {
+            enclosing: Some(enclosing),
+            ..Default::default()
+        }
+    }
+
+    /// Binds a new variable. Redefining an existing name is allowed and
+    /// simply replaces the previous value.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Reads a variable bound directly in this scope, ignoring enclosing ones.
+    pub fn get_local(&self, name: &str) -> Option<Value> {
+        self.values.get(name).cloned()
+    }
+
+    /// Every variable bound directly in this scope, sorted by name.
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        let mut bindings: Vec<(String, Value)> = self.values.iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|(left, _), (right, _)| left.cmp(right));
+        bindings
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.values.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        // Walk the chain iteratively so deeply nested scopes don't recurse.
+        let mut scope = self.enclosing.clone();
+        while let Some(environment) = scope {
+            let environment = environment.borrow();
+            if let Some(value) = environment.values.get(&name.lexeme) {
+                return Ok(value.clone());
+            }
+            scope = environment.enclosing.clone();
+        }
+
+        Err(undefined_variable(name))
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+
+        let mut scope = self.enclosing.clone();
+        while let Some(environment) = scope {
+            let mut environment = environment.borrow_mut();
+            if let Some(slot) = environment.values.get_mut(&name.lexeme) {
+                *slot = value;
+                return Ok(());
+            }
+            scope = environment.enclosing.clone();
+        }
+
+        Err(undefined_variable(name))
+    }
+}
+
+/// Accessors for variables whose scope distance was computed by the
+/// resolver. They jump straight to the right scope instead of searching.
+impl Environment {
+    pub fn ancestor(environment: &Rc<RefCell<Environment>>, distance: usize) -> Rc<RefCell<Environment>> {
+        let mut environment = environment.clone();
+        for _ in 0..distance {
+            let enclosing = environment.borrow().enclosing.clone()
+                .expect("resolved distance should not exceed the scope chain");
+            environment = enclosing;
+        }
+        environment
+    }
+
+    pub fn get_at(environment: &Rc<RefCell<Environment>>, distance: usize, name: &Token) -> Result<Value, RuntimeError> {
+        let ancestor = Environment::ancestor(environment, distance);
+        let ancestor = ancestor.borrow();
+        match ancestor.values.get(&name.lexeme) {
+            Some(value) => Ok(value.clone()),
+            None => Err(undefined_variable(name)),
+        }
+    }
+
+    pub fn assign_at(environment: &Rc<RefCell<Environment>>, distance: usize, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        let ancestor = Environment::ancestor(environment, distance);
+        let mut ancestor = ancestor.borrow_mut();
+        match ancestor.values.get_mut(&name.lexeme) {
+            Some(slot) => {
+                *slot = value;
+                Ok(())
+            }
+            None => Err(undefined_variable(name)),
+        }
+    }
+}
+
+fn undefined_variable(name: &Token) -> RuntimeError {
+    RuntimeError::new(name, format!("Undefined variable '{}'.", name.lexeme).as_str())
+}
@@ -1,108 +1,360 @@
-use std::any::Any;
+use crate::span::Span;
 use crate::token::Token;
+use crate::value::LiteralValue;
+#[derive(Clone)]
 pub enum Expr {
+	Assign(Assign),
 	Binary(Binary),
+	Call(Call),
+	Get(Get),
 	Grouping(Grouping),
 	Literal(Literal),
+	Logical(Logical),
+	Set(Set),
+	Super(Super),
+	This(This),
 	Unary(Unary),
+	Variable(Variable),
 }
 
 pub trait Visitor<R> {
-	fn visit_binary(&self, element: &Binary) -> R ;
-	fn visit_grouping(&self, element: &Grouping) -> R ;
-	fn visit_literal(&self, element: &Literal) -> R ;
-	fn visit_unary(&self, element: &Unary) -> R ;
+	fn visit_assign(&mut self, element: &Assign) -> R ;
+	fn visit_binary(&mut self, element: &Binary) -> R ;
+	fn visit_call(&mut self, element: &Call) -> R ;
+	fn visit_get(&mut self, element: &Get) -> R ;
+	fn visit_grouping(&mut self, element: &Grouping) -> R ;
+	fn visit_literal(&mut self, element: &Literal) -> R ;
+	fn visit_logical(&mut self, element: &Logical) -> R ;
+	fn visit_set(&mut self, element: &Set) -> R ;
+	fn visit_super(&mut self, element: &Super) -> R ;
+	fn visit_this(&mut self, element: &This) -> R ;
+	fn visit_unary(&mut self, element: &Unary) -> R ;
+	fn visit_variable(&mut self, element: &Variable) -> R ;
 }
 
 pub trait VisitedElement {
-	fn accept<S: Visitor<R>, R>(&self, visitor: S) -> R;
+	fn accept<S: Visitor<R>, R>(&self, visitor: &mut S) -> R;
 }
 
+#[derive(Clone)]
+pub struct Assign {
+	pub name: Token,
+	pub value: Box<Expr>,
+	pub id: usize,
+	pub span: Span,
+}
+
+impl Assign {
+	pub fn new(name: Token, value: Box<Expr>, id: usize, span: Span, ) -> Self {
+		Assign {
+			name,
+			value,
+			id,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Assign {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_assign(self)
+	}
+}
+
+#[derive(Clone)]
 pub struct Binary {
 	pub left: Box<Expr>,
 	pub operator: Token,
 	pub right: Box<Expr>,
+	pub span: Span,
 }
 
 impl Binary {
-	pub fn new(left: Box<Expr>, operator: Token, right: Box<Expr>, ) -> Self {
+	pub fn new(left: Box<Expr>, operator: Token, right: Box<Expr>, span: Span, ) -> Self {
 		Binary {
 			left,
 			operator,
 			right,
+			span,
 		}
 	}
 }
 
 impl VisitedElement for Binary {
-	fn accept<V: Visitor<R>, R>(&self, visitor: V) -> R {
-		visitor.visit_binary(&self)
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_binary(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Call {
+	pub callee: Box<Expr>,
+	pub paren: Token,
+	pub arguments: Vec<Expr>,
+	pub span: Span,
+}
+
+impl Call {
+	pub fn new(callee: Box<Expr>, paren: Token, arguments: Vec<Expr>, span: Span, ) -> Self {
+		Call {
+			callee,
+			paren,
+			arguments,
+			span,
+		}
 	}
 }
 
+impl VisitedElement for Call {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_call(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Get {
+	pub object: Box<Expr>,
+	pub name: Token,
+	pub span: Span,
+}
+
+impl Get {
+	pub fn new(object: Box<Expr>, name: Token, span: Span, ) -> Self {
+		Get {
+			object,
+			name,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Get {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_get(self)
+	}
+}
+
+#[derive(Clone)]
 pub struct Grouping {
 	pub expression: Box<Expr>,
+	pub span: Span,
 }
 
 impl Grouping {
-	pub fn new(expression: Box<Expr>, ) -> Self {
+	pub fn new(expression: Box<Expr>, span: Span, ) -> Self {
 		Grouping {
 			expression,
+			span,
 		}
 	}
 }
 
 impl VisitedElement for Grouping {
-	fn accept<V: Visitor<R>, R>(&self, visitor: V) -> R {
-		visitor.visit_grouping(&self)
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_grouping(self)
 	}
 }
 
+#[derive(Clone)]
 pub struct Literal {
-	pub value: Option<String>,
+	pub value: LiteralValue,
+	pub span: Span,
 }
 
 impl Literal {
-	pub fn new(value: Option<String>, ) -> Self {
+	pub fn new(value: LiteralValue, span: Span, ) -> Self {
 		Literal {
 			value,
+			span,
 		}
 	}
 }
 
 impl VisitedElement for Literal {
-	fn accept<V: Visitor<R>, R>(&self, visitor: V) -> R {
-		visitor.visit_literal(&self)
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_literal(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Logical {
+	pub left: Box<Expr>,
+	pub operator: Token,
+	pub right: Box<Expr>,
+	pub span: Span,
+}
+
+impl Logical {
+	pub fn new(left: Box<Expr>, operator: Token, right: Box<Expr>, span: Span, ) -> Self {
+		Logical {
+			left,
+			operator,
+			right,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Logical {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_logical(self)
 	}
 }
 
+#[derive(Clone)]
+pub struct Set {
+	pub object: Box<Expr>,
+	pub name: Token,
+	pub value: Box<Expr>,
+	pub span: Span,
+}
+
+impl Set {
+	pub fn new(object: Box<Expr>, name: Token, value: Box<Expr>, span: Span, ) -> Self {
+		Set {
+			object,
+			name,
+			value,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Set {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_set(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Super {
+	pub keyword: Token,
+	pub method: Token,
+	pub id: usize,
+	pub span: Span,
+}
+
+impl Super {
+	pub fn new(keyword: Token, method: Token, id: usize, span: Span, ) -> Self {
+		Super {
+			keyword,
+			method,
+			id,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Super {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_super(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct This {
+	pub keyword: Token,
+	pub id: usize,
+	pub span: Span,
+}
+
+impl This {
+	pub fn new(keyword: Token, id: usize, span: Span, ) -> Self {
+		This {
+			keyword,
+			id,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for This {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_this(self)
+	}
+}
+
+#[derive(Clone)]
 pub struct Unary {
 	pub operator: Token,
 	pub right: Box<Expr>,
+	pub span: Span,
 }
 
 impl Unary {
-	pub fn new(operator: Token, right: Box<Expr>, ) -> Self {
+	pub fn new(operator: Token, right: Box<Expr>, span: Span, ) -> Self {
 		Unary {
 			operator,
 			right,
+			span,
 		}
 	}
 }
 
 impl VisitedElement for Unary {
-	fn accept<V: Visitor<R>, R>(&self, visitor: V) -> R {
-		visitor.visit_unary(&self)
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_unary(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Variable {
+	pub name: Token,
+	pub id: usize,
+	pub span: Span,
+}
+
+impl Variable {
+	pub fn new(name: Token, id: usize, span: Span, ) -> Self {
+		Variable {
+			name,
+			id,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Variable {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_variable(self)
 	}
 }
 
 impl VisitedElement for Expr {
-	fn accept<S: Visitor<R>, R>(&self, visitor: S) -> R {
+	fn accept<S: Visitor<R>, R>(&self, visitor: &mut S) -> R {
 		match self {
+			 Expr::Assign(b) => {b.accept(visitor)},
 			 Expr::Binary(b) => {b.accept(visitor)},
+			 Expr::Call(b) => {b.accept(visitor)},
+			 Expr::Get(b) => {b.accept(visitor)},
 			 Expr::Grouping(b) => {b.accept(visitor)},
 			 Expr::Literal(b) => {b.accept(visitor)},
+			 Expr::Logical(b) => {b.accept(visitor)},
+			 Expr::Set(b) => {b.accept(visitor)},
+			 Expr::Super(b) => {b.accept(visitor)},
+			 Expr::This(b) => {b.accept(visitor)},
 			 Expr::Unary(b) => {b.accept(visitor)},
+			 Expr::Variable(b) => {b.accept(visitor)},
+		}
+	}
+}
+
+impl Expr {
+	#[allow(dead_code)]
+	pub fn span(&self) -> Span {
+		match self {
+			 Expr::Assign(b) => {b.span},
+			 Expr::Binary(b) => {b.span},
+			 Expr::Call(b) => {b.span},
+			 Expr::Get(b) => {b.span},
+			 Expr::Grouping(b) => {b.span},
+			 Expr::Literal(b) => {b.span},
+			 Expr::Logical(b) => {b.span},
+			 Expr::Set(b) => {b.span},
+			 Expr::Super(b) => {b.span},
+			 Expr::This(b) => {b.span},
+			 Expr::Unary(b) => {b.span},
+			 Expr::Variable(b) => {b.span},
 		}
 	}
 }
@@ -0,0 +1,749 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+use crate::diagnostic::{Diagnostic, Label, RUNTIME_ERROR};
+use crate::environment::Environment;
+use crate::expression;
+use crate::expression::{Assign, Binary, Call, Expr, Get, Grouping, Literal, Logical, Set, Super, This, Unary, Variable, VisitedElement};
+use crate::lox_callable::LoxCallable;
+use crate::lox_class::LoxClass;
+use crate::lox_function::LoxFunction;
+use crate::lox_instance::LoxInstance;
+use crate::natives::Clock;
+use crate::span::Span;
+use crate::statement;
+use crate::statement::{Block, Class, Expression, Function, If, Print, Return, Stmt, Var, While};
+use crate::statement::VisitedElement as VisitedStmt;
+use crate::token::Token;
+use crate::token_type::TokenType::{Bang, BangEqual, EqualEqual, Greater, GreaterEqual, Less, LessEqual, Minus, Or, Plus, Slash, Star};
+use crate::value::Value;
+
+/// How deep calls can nest before the program is stopped with a stack
+/// overflow error.
+const MAX_CALL_DEPTH: usize = 1000;
+
+/// How deep statements and expressions can nest, counting across calls,
+/// before the program is stopped with a stack overflow error. The parser
+/// bounds the nesting within a function, but calls stack it up.
+const MAX_DEPTH: usize = 10_000;
+
+/// Stack size for the thread running the interpreter, enough for
+/// `MAX_CALL_DEPTH` nested calls and `MAX_DEPTH` nested nodes in a debug
+/// build with room to spare.
+pub const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    /// Where the error happened: the expression that failed, or the name
+    /// at fault.
+    pub span: Span,
+    pub message: String,
+    pub labels: Vec<Label>,
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, message: &str) -> Self {
+        RuntimeError::at(token.span, message)
+    }
+
+    pub fn at(span: Span, message: &str) -> Self {
+        RuntimeError {
+            span,
+            message: message.to_string(),
+            labels: vec![],
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push(Label { span, message: message.to_string() });
+        self
+    }
+}
+
+impl From<RuntimeError> for Diagnostic {
+    fn from(error: RuntimeError) -> Self {
+        let mut diagnostic = Diagnostic::error(RUNTIME_ERROR, &error.message, error.span);
+        diagnostic.labels = error.labels;
+        diagnostic
+    }
+}
+
+/// Why the execution of a statement stopped before reaching its end.
+pub enum Unwind {
+    Error(RuntimeError),
+    /// A `return` statement, carrying the value back to the call site.
+    Return(Value),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+pub struct Interpreter {
+    pub globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    /// Scope distance of each resolved local variable reference, keyed by
+    /// the id of the expression node.
+    locals: HashMap<usize, usize>,
+    /// Where `print` writes its values.
+    out: Box<dyn Write>,
+    /// Number of calls in progress, to fail cleanly on runaway recursion.
+    call_depth: usize,
+    /// Number of statements and expressions being run, calls included.
+    depth: usize,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+        globals.borrow_mut().define("clock", Value::Callable(Rc::new(Clock)));
+
+        Interpreter {
+            globals: globals.clone(),
+            environment: globals,
+            locals: HashMap::new(),
+            out: Box::new(io::stdout()),
+            call_depth: 0,
+            depth: 0,
+        }
+    }
+
+    /// Records how many scopes separate a variable reference from its
+    /// declaration. Called by the resolver.
+    pub fn resolve(&mut self, id: usize, depth: usize) {
+        self.locals.insert(id, depth);
+    }
+
+    /// How many scopes separate a variable reference from its declaration,
+    /// or `None` for a global.
+    pub fn local_depth(&self, id: usize) -> Option<usize> {
+        self.locals.get(&id).copied()
+    }
+
+    fn look_up_variable(&self, name: &Token, id: usize) -> Result<Value, RuntimeError> {
+        match self.local_depth(id) {
+            Some(distance) => Environment::get_at(&self.environment, distance, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    /// Runs the statements in order, stopping at the first runtime error.
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        for statement in statements {
+            match self.execute(statement) {
+                Ok(()) => {}
+                Err(Unwind::Error(error)) => return Err(error),
+                // A top-level return simply ends the program.
+                Err(Unwind::Return(_)) => return Ok(()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates a lone expression, as the prompt does to echo its value.
+    pub fn interpret_expression(&mut self, expression: &Expr) -> Result<Value, RuntimeError> {
+        self.evaluate(expression)
+    }
+
+    fn execute(&mut self, statement: &Stmt) -> Result<(), Unwind> {
+        if self.depth >= MAX_DEPTH {
+            return Err(RuntimeError::at(statement.span(), "Stack overflow.").into());
+        }
+        self.depth += 1;
+        let result = statement.accept(self);
+        self.depth -= 1;
+        result
+    }
+
+    pub fn execute_block(&mut self, statements: &[Stmt], environment: Environment) -> Result<(), Unwind> {
+        let previous = self.environment.clone();
+        self.environment = Rc::new(RefCell::new(environment));
+        let result = statements.iter().try_for_each(|statement| self.execute(statement));
+        self.environment = previous;
+        result
+    }
+
+    fn evaluate(&mut self, expression: &Expr) -> Result<Value, RuntimeError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(RuntimeError::at(expression.span(), "Stack overflow."));
+        }
+        self.depth += 1;
+        let result = expression.accept(self);
+        self.depth -= 1;
+        result
+    }
+}
+
+/// An error on the operands of an operation, spanning the whole expression
+/// with the operator labelled.
+fn operand_error(span: Span, operator: &Token, message: &str) -> RuntimeError {
+    RuntimeError::at(span, message).with_label(operator.span, "operator applied here")
+}
+
+/// An error making a call. Like jlox it is reported at the closing paren,
+/// so a call spread over several lines fails on the line where it ends,
+/// with the callee labelled.
+fn call_error(element: &Call, message: &str) -> RuntimeError {
+    RuntimeError::new(&element.paren, message).with_label(element.callee.span(), "called here")
+}
+
+fn number_operand(span: Span, operator: &Token, operand: &Value) -> Result<f64, RuntimeError> {
+    match operand {
+        Value::Number(n) => Ok(*n),
+        _ => Err(operand_error(span, operator, "Operand must be a number.")),
+    }
+}
+
+fn number_operands(span: Span, operator: &Token, left: &Value, right: &Value) -> Result<(f64, f64), RuntimeError> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => Ok((*l, *r)),
+        _ => Err(operand_error(span, operator, "Operands must be numbers.")),
+    }
+}
+
+impl expression::Visitor<Result<Value, RuntimeError>> for Interpreter {
+    fn visit_assign(&mut self, element: &Assign) -> Result<Value, RuntimeError> {
+        let value = self.evaluate(&element.value)?;
+        match self.local_depth(element.id) {
+            Some(distance) => Environment::assign_at(&self.environment, distance, &element.name, value.clone())?,
+            None => self.globals.borrow_mut().assign(&element.name, value.clone())?,
+        }
+        Ok(value)
+    }
+
+    fn visit_binary(&mut self, element: &Binary) -> Result<Value, RuntimeError> {
+        let left = self.evaluate(&element.left)?;
+        let right = self.evaluate(&element.right)?;
+        let operator = &element.operator;
+
+        match operator.token_type {
+            Minus => {
+                let (l, r) = number_operands(element.span, operator, &left, &right)?;
+                Ok(Value::Number(l - r))
+            }
+            Slash => {
+                let (l, r) = number_operands(element.span, operator, &left, &right)?;
+                Ok(Value::Number(l / r))
+            }
+            Star => {
+                let (l, r) = number_operands(element.span, operator, &left, &right)?;
+                Ok(Value::Number(l * r))
+            }
+            Plus => match (left, right) {
+                (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l + r)),
+                (Value::Str(l), Value::Str(r)) => Ok(Value::Str(l + &r)),
+                _ => Err(operand_error(element.span, operator, "Operands must be two numbers or two strings.")),
+            },
+            Greater => {
+                let (l, r) = number_operands(element.span, operator, &left, &right)?;
+                Ok(Value::Bool(l > r))
+            }
+            GreaterEqual => {
+                let (l, r) = number_operands(element.span, operator, &left, &right)?;
+                Ok(Value::Bool(l >= r))
+            }
+            Less => {
+                let (l, r) = number_operands(element.span, operator, &left, &right)?;
+                Ok(Value::Bool(l < r))
+            }
+            LessEqual => {
+                let (l, r) = number_operands(element.span, operator, &left, &right)?;
+                Ok(Value::Bool(l <= r))
+            }
+            BangEqual => Ok(Value::Bool(left != right)),
+            EqualEqual => Ok(Value::Bool(left == right)),
+            _ => Err(RuntimeError::new(operator, "Unknown binary operator.")),
+        }
+    }
+
+    fn visit_call(&mut self, element: &Call) -> Result<Value, RuntimeError> {
+        let callee = self.evaluate(&element.callee)?;
+
+        let mut arguments: Vec<Value> = vec![];
+        for argument in element.arguments.iter() {
+            arguments.push(self.evaluate(argument)?);
+        }
+
+        let function: Rc<dyn LoxCallable> = match callee {
+            Value::Callable(function) => function,
+            Value::Class(class) => class,
+            _ => return Err(call_error(element, "Can only call functions and classes.")),
+        };
+
+        if arguments.len() != function.arity() {
+            let message = format!("Expected {} arguments but got {}.", function.arity(), arguments.len());
+            return Err(call_error(element, &message));
+        }
+
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(call_error(element, "Stack overflow."));
+        }
+        self.call_depth += 1;
+        let result = function.call(self, arguments);
+        self.call_depth -= 1;
+        result
+    }
+
+    fn visit_get(&mut self, element: &Get) -> Result<Value, RuntimeError> {
+        match self.evaluate(&element.object)? {
+            Value::Instance(instance) => LoxInstance::get(&instance, &element.name),
+            _ => Err(RuntimeError::new(&element.name, "Only instances have properties.")),
+        }
+    }
+
+    fn visit_grouping(&mut self, element: &Grouping) -> Result<Value, RuntimeError> {
+        self.evaluate(&element.expression)
+    }
+
+    fn visit_literal(&mut self, element: &Literal) -> Result<Value, RuntimeError> {
+        Ok(Value::from(&element.value))
+    }
+
+    fn visit_logical(&mut self, element: &Logical) -> Result<Value, RuntimeError> {
+        let left = self.evaluate(&element.left)?;
+
+        // Short-circuit, yielding the operand that decided the result.
+        if element.operator.token_type == Or {
+            if left.is_truthy() {
+                return Ok(left);
+            }
+        } else if !left.is_truthy() {
+            return Ok(left);
+        }
+
+        self.evaluate(&element.right)
+    }
+
+    fn visit_set(&mut self, element: &Set) -> Result<Value, RuntimeError> {
+        let Value::Instance(instance) = self.evaluate(&element.object)? else {
+            return Err(RuntimeError::new(&element.name, "Only instances have fields."));
+        };
+
+        let value = self.evaluate(&element.value)?;
+        instance.borrow_mut().set(&element.name, value.clone());
+        Ok(value)
+    }
+
+    fn visit_super(&mut self, element: &Super) -> Result<Value, RuntimeError> {
+        let distance = self.local_depth(element.id)
+            .expect("the resolver should have resolved every 'super'");
+
+        let Value::Class(superclass) = Environment::get_at(&self.environment, distance, &element.keyword)? else {
+            return Err(RuntimeError::new(&element.keyword, "Superclass must be a class."));
+        };
+
+        // `this` is always bound in the scope right inside the one for `super`.
+        let this = Environment::ancestor(&self.environment, distance - 1).borrow().get_local("this");
+        let Some(Value::Instance(instance)) = this else {
+            return Err(RuntimeError::new(&element.keyword, "Can't use 'super' outside of a method."));
+        };
+
+        match superclass.find_method(&element.method.lexeme) {
+            Some(method) => Ok(Value::Callable(Rc::new(method.bind(instance)))),
+            None => Err(RuntimeError::new(&element.method, format!("Undefined property '{}'.", element.method.lexeme).as_str())),
+        }
+    }
+
+    fn visit_this(&mut self, element: &This) -> Result<Value, RuntimeError> {
+        self.look_up_variable(&element.keyword, element.id)
+    }
+
+    fn visit_unary(&mut self, element: &Unary) -> Result<Value, RuntimeError> {
+        let right = self.evaluate(&element.right)?;
+
+        match element.operator.token_type {
+            Bang => Ok(Value::Bool(!right.is_truthy())),
+            Minus => Ok(Value::Number(-number_operand(element.span, &element.operator, &right)?)),
+            _ => Err(RuntimeError::new(&element.operator, "Unknown unary operator.")),
+        }
+    }
+    fn visit_variable(&mut self, element: &Variable) -> Result<Value, RuntimeError> {
+        self.look_up_variable(&element.name, element.id)
+    }
+}
+
+impl statement::Visitor<Result<(), Unwind>> for Interpreter {
+    fn visit_block(&mut self, element: &Block) -> Result<(), Unwind> {
+        let environment = Environment::new_enclosed(self.environment.clone());
+        self.execute_block(&element.statements, environment)
+    }
+
+    fn visit_class(&mut self, element: &Class) -> Result<(), Unwind> {
+        let superclass = match &element.superclass {
+            Some(superclass) => match self.look_up_variable(&superclass.name, superclass.id)? {
+                Value::Class(class) => Some(class),
+                _ => return Err(RuntimeError::new(&superclass.name, "Superclass must be a class.").into()),
+            },
+            None => None,
+        };
+
+        self.environment.borrow_mut().define(&element.name.lexeme, Value::Nil);
+
+        // Methods of a subclass close over a scope where `super` is bound.
+        let enclosing = self.environment.clone();
+        if let Some(superclass) = &superclass {
+            let mut environment = Environment::new_enclosed(enclosing.clone());
+            environment.define("super", Value::Class(superclass.clone()));
+            self.environment = Rc::new(RefCell::new(environment));
+        }
+
+        let mut methods: HashMap<String, Rc<LoxFunction>> = HashMap::new();
+        for method in element.methods.iter() {
+            let is_initializer = method.name.lexeme == "init";
+            let function = LoxFunction::new(method.clone(), self.environment.clone(), is_initializer);
+            methods.insert(method.name.lexeme.clone(), Rc::new(function));
+        }
+
+        let class = LoxClass::new(element.name.lexeme.clone(), superclass, methods);
+        self.environment = enclosing;
+        self.environment.borrow_mut().assign(&element.name, Value::Class(Rc::new(class)))?;
+        Ok(())
+    }
+
+    fn visit_expression(&mut self, element: &Expression) -> Result<(), Unwind> {
+        self.evaluate(&element.expression)?;
+        Ok(())
+    }
+
+    fn visit_function(&mut self, element: &Function) -> Result<(), Unwind> {
+        let function = LoxFunction::new(element.clone(), self.environment.clone(), false);
+        self.environment.borrow_mut().define(&element.name.lexeme, Value::Callable(Rc::new(function)));
+        Ok(())
+    }
+
+    fn visit_if(&mut self, element: &If) -> Result<(), Unwind> {
+        if self.evaluate(&element.condition)?.is_truthy() {
+            self.execute(&element.then_branch)
+        } else if let Some(else_branch) = &element.else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn visit_print(&mut self, element: &Print) -> Result<(), Unwind> {
+        let value = self.evaluate(&element.expression)?;
+        let _ = writeln!(self.out, "{value}");
+        Ok(())
+    }
+
+    fn visit_return(&mut self, element: &Return) -> Result<(), Unwind> {
+        let value = match &element.value {
+            Some(value) => self.evaluate(value)?,
+            None => Value::Nil,
+        };
+        Err(Unwind::Return(value))
+    }
+
+    fn visit_var(&mut self, element: &Var) -> Result<(), Unwind> {
+        let value = match &element.initializer {
+            Some(initializer) => self.evaluate(initializer)?,
+            None => Value::Nil,
+        };
+        self.environment.borrow_mut().define(&element.name.lexeme, value);
+        Ok(())
+    }
+
+    fn visit_while(&mut self, element: &While) -> Result<(), Unwind> {
+        while self.evaluate(&element.condition)?.is_truthy() {
+            self.execute(&element.body)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::Resolver;
+    use crate::test_support::parse;
+
+    /// Collects what `print` writes, shared with the test that reads it.
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs a program and returns the lines it printed, along with the
+    /// runtime error that stopped it if any.
+    fn run(source: &str) -> (Vec<String>, Result<(), RuntimeError>) {
+        let statements = parse(source);
+
+        let output = Output::default();
+        let mut interpreter = Interpreter::new();
+        interpreter.out = Box::new(output.clone());
+        let mut resolver = Resolver::new(&mut interpreter);
+        resolver.resolve(&statements);
+        assert!(resolver.diagnostics().is_empty(), "test programs should resolve");
+
+        let result = interpreter.interpret(&statements);
+        let printed = String::from_utf8(output.0.take()).unwrap();
+        (printed.lines().map(str::to_string).collect(), result)
+    }
+
+    fn printed(source: &str) -> Vec<String> {
+        let (printed, result) = run(source);
+        if let Err(error) = result {
+            panic!("unexpected runtime error in {source:?}: {}", error.message);
+        }
+        printed
+    }
+
+    #[test]
+    fn evaluates_expressions() {
+        let cases = [
+            ("1 + 2 * 3", "7"),
+            ("(1 + 2) * 3", "9"),
+            ("10 / 4 - 1", "1.5"),
+            ("-3 - -3", "0"),
+            ("1 / 0", "Infinity"),
+            ("\"con\" + \"cat\"", "concat"),
+            ("2 < 3", "true"),
+            ("3 <= 2", "false"),
+            ("nil == nil", "true"),
+            ("nil == false", "false"),
+            ("1 == \"1\"", "false"),
+            ("\"a\" != \"a\"", "false"),
+            ("!nil", "true"),
+            ("!0", "false"),
+            ("!\"\"", "false"),
+            ("nil or \"default\"", "default"),
+            ("0 and \"zero is truthy\"", "zero is truthy"),
+            ("false and undefined", "false"),
+        ];
+        for (expression, value) in cases {
+            assert_eq!(printed(&format!("print {expression};")), vec![value], "value of {expression}");
+        }
+    }
+
+    #[test]
+    fn reports_runtime_errors_on_their_line() {
+        let cases = [
+            ("print \"a\" + 1;", 1, "Operands must be two numbers or two strings."),
+            ("print 1;\nprint -\"x\";", 2, "Operand must be a number."),
+            ("\n\nprint 1 < nil;", 3, "Operands must be numbers."),
+            ("print missing;", 1, "Undefined variable 'missing'."),
+            ("\"not a function\"();", 1, "Can only call functions and classes."),
+            ("fun f(a) {}\nf();", 2, "Expected 1 arguments but got 0."),
+            ("class A {}\nprint A().field;", 2, "Undefined property 'field'."),
+            ("var NotAClass = 1;\nclass B < NotAClass {}", 2, "Superclass must be a class."),
+        ];
+        for (source, line, message) in cases {
+            let (_, result) = run(source);
+            let error = result.err().unwrap_or_else(|| panic!("expected an error from {source:?}"));
+            assert_eq!((error.span.line, error.message.as_str()), (line, message), "error from {source:?}");
+        }
+    }
+
+    #[test]
+    fn spans_errors_over_the_whole_expression() {
+        let (_, result) = run("var a = 1;\nprint a + \"x\";");
+        let error = result.expect_err("expected an operand error");
+        assert_eq!((error.span.line, error.span.column, error.span.end - error.span.start), (2, 7, 7));
+        assert_eq!(error.labels.len(), 1);
+        assert_eq!((error.labels[0].span.column, error.labels[0].message.as_str()), (9, "operator applied here"));
+
+        let (_, result) = run("fun f() {}\nf(1, 2);");
+        let error = result.expect_err("expected an arity error");
+        assert_eq!((error.span.line, error.span.column, error.span.end - error.span.start), (2, 7, 1));
+        assert_eq!((error.labels[0].span.column, error.labels[0].message.as_str()), (1, "called here"));
+    }
+
+    #[test]
+    fn reports_call_errors_at_the_closing_paren() {
+        let (_, result) = run("fun f(a) {}\nf(\n1,\n2\n);");
+        let error = result.expect_err("expected an arity error");
+        assert_eq!((error.span.line, error.span.column), (5, 1));
+        assert_eq!((error.labels[0].span.line, error.labels[0].message.as_str()), (2, "called here"));
+    }
+
+    #[test]
+    fn stops_at_the_first_runtime_error() {
+        let (printed, result) = run("print 1;\nprint nil + 1;\nprint 2;");
+        assert_eq!(printed, vec!["1"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn reports_runaway_recursion() {
+        // Test threads get a small stack, so run on one like rlox's.
+        let result = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(|| run("fun f(n) { return f(n + 1); }\nf(0);").1)
+            .unwrap()
+            .join()
+            .unwrap();
+        let error = result.expect_err("expected a stack overflow");
+        assert_eq!((error.span.line, error.message.as_str()), (1, "Stack overflow."));
+
+        assert_eq!(printed("fun f(n) { if (n == 0) return 0; return f(n - 1); } print f(100);"), vec!["0"]);
+    }
+
+    #[test]
+    fn reports_deep_nesting_across_calls() {
+        // Each call nests a few dozen statements, which overflows well
+        // before the call depth does.
+        let nested = format!("{}return f(n + 1);{}", "{ if (true) ".repeat(40), "}".repeat(40));
+        let source = format!("fun f(n) {{\n{nested}\n}}\nf(0);");
+        let result = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(move || run(&source).1)
+            .unwrap()
+            .join()
+            .unwrap();
+        let error = result.expect_err("expected a stack overflow");
+        assert_eq!((error.span.line, error.message.as_str()), (2, "Stack overflow."));
+    }
+
+    #[test]
+    fn branches_on_truthiness() {
+        let source = "
+            if (true) print \"then\"; else print \"else\";
+            if (nil) print \"then\"; else print \"else\";
+            if (0) print \"zero is true\";
+            if (false) print \"skipped\";
+            if (true) if (false) print 1; else print \"inner else\";
+        ";
+        assert_eq!(printed(source), vec!["then", "else", "zero is true", "inner else"]);
+    }
+
+    #[test]
+    fn loops_while_the_condition_holds() {
+        let source = "
+            var i = 0;
+            while (i < 3) { print i; i = i + 1; }
+            while (false) print \"never\";
+        ";
+        assert_eq!(printed(source), vec!["0", "1", "2"]);
+    }
+
+    #[test]
+    fn desugars_for_into_while() {
+        let source = "
+            for (var i = 0; i < 3; i = i + 1) print i;
+            var j = 10;
+            for (; j < 12;) { print j; j = j + 1; }
+        ";
+        assert_eq!(printed(source), vec!["0", "1", "2", "10", "11"]);
+
+        // The increment runs after the body, even when the body changes
+        // the loop variable.
+        let source = "for (var i = 0; i < 5; i = i * 2) { i = i + 1; print i; }";
+        assert_eq!(printed(source), vec!["1", "3"]);
+
+        // A missing condition loops until something breaks out.
+        let source = "
+            fun first(limit) { for (var i = 0;; i = i + 1) if (i * i > limit) return i; }
+            print first(10);
+        ";
+        assert_eq!(printed(source), vec!["4"]);
+
+        // The loop variable belongs to the loop.
+        let (printed, result) = run("var i = \"outer\";\nfor (var i = 0; i < 1; i = i + 1) print i;\nprint i;");
+        assert_eq!(printed, vec!["0", "outer"]);
+        assert!(result.is_ok());
+        let (_, result) = run("for (var i = 0; i < 1; i = i + 1) {}\nprint i;");
+        let error = result.expect_err("expected the loop variable to be out of scope");
+        assert_eq!((error.span.line, error.message.as_str()), (2, "Undefined variable 'i'."));
+    }
+
+    #[test]
+    fn blocks_shadow_outer_variables() {
+        let source = "
+            var a = \"global\";
+            {
+                var a = \"outer\";
+                { var a = \"inner\"; print a; }
+                print a;
+            }
+            print a;
+        ";
+        assert_eq!(printed(source), vec!["inner", "outer", "global"]);
+    }
+
+    #[test]
+    fn assigns_right_to_left() {
+        let source = "
+            var a = 1;
+            var b = 2;
+            print a = b = 3;
+            print a;
+            print b;
+            { var c; a = c = \"local\"; print c; }
+            print a;
+        ";
+        assert_eq!(printed(source), vec!["3", "3", "3", "local", "local"]);
+    }
+
+    #[test]
+    fn refuses_to_assign_undeclared_variables() {
+        let (_, result) = run("var a = 1;\nb = 2;");
+        let error = result.expect_err("expected an undefined variable error");
+        assert_eq!((error.span.line, error.message.as_str()), (2, "Undefined variable 'b'."));
+    }
+
+    #[test]
+    fn closures_capture_their_environment() {
+        let source = "
+            fun counter() {
+                var count = 0;
+                fun increment() { count = count + 1; return count; }
+                return increment;
+            }
+            var next = counter();
+            next();
+            print next();";
+        assert_eq!(printed(source), vec!["2"]);
+    }
+
+    #[test]
+    fn init_returns_the_instance() {
+        let source = "
+            class Point {
+                init(x) { this.x = x; return; }
+            }
+            var p = Point(1);
+            print p;
+            print p.init(2) == p;
+            print p.x;";
+        assert_eq!(printed(source), vec!["Point instance", "true", "2"]);
+    }
+
+    #[test]
+    fn super_dispatches_along_the_chain() {
+        let source = "
+            class A {
+                name() { return \"A\"; }
+                greet() { return \"hi from \" + this.name(); }
+            }
+            class B < A {
+                name() { return \"B\"; }
+                greet() { return super.greet() + \"!\"; }
+            }
+            class C < B {}
+            class D < C {
+                greet() { return \"D says \" + super.greet(); }
+            }
+            print C().greet();
+            print D().greet();";
+        assert_eq!(printed(source), vec!["hi from B!", "D says hi from B!"]);
+    }
+}
@@ -0,0 +1,457 @@
+use std::fmt;
+
+/// A JSON document, for the machine-readable outputs of the CLI. Objects
+/// keep their keys in insertion order so the output is stable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Raised when a document is not valid JSON, or not shaped as expected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonError {
+    pub message: String,
+}
+
+impl JsonError {
+    pub fn new(message: &str) -> Self {
+        JsonError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Json {
+    pub fn object(members: Vec<(&str, Json)>) -> Self {
+        Json::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser { text, current: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.current < text.len() {
+            return Err(parser.error("Expect end of input."));
+        }
+        Ok(value)
+    }
+
+    /// The member `key` of an object, or an error naming it if it is missing.
+    pub fn field(&self, key: &str) -> Result<&Json, JsonError> {
+        let Json::Object(members) = self else {
+            return Err(JsonError::new(&format!("Expect an object with a '{key}' member.")));
+        };
+        members.iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+            .ok_or_else(|| JsonError::new(&format!("Missing member '{key}'.")))
+    }
+
+    pub fn as_str(&self) -> Result<&str, JsonError> {
+        match self {
+            Json::String(s) => Ok(s),
+            _ => Err(JsonError::new(&format!("Expect a string, got {self}."))),
+        }
+    }
+
+    pub fn as_f64(&self) -> Result<f64, JsonError> {
+        match self {
+            Json::Number(n) => Ok(*n),
+            _ => Err(JsonError::new(&format!("Expect a number, got {self}."))),
+        }
+    }
+
+    pub fn as_usize(&self) -> Result<usize, JsonError> {
+        let n = self.as_f64()?;
+        if n < 0.0 || n.fract() != 0.0 {
+            return Err(JsonError::new(&format!("Expect a positive integer, got {n}.")));
+        }
+        Ok(n as usize)
+    }
+
+    pub fn as_bool(&self) -> Result<bool, JsonError> {
+        match self {
+            Json::Bool(b) => Ok(*b),
+            _ => Err(JsonError::new(&format!("Expect a boolean, got {self}."))),
+        }
+    }
+
+    pub fn as_array(&self) -> Result<&[Json], JsonError> {
+        match self {
+            Json::Array(items) => Ok(items),
+            _ => Err(JsonError::new(&format!("Expect an array, got {self}."))),
+        }
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as f64)
+    }
+}
+
+/// Serializes on a single line, so that documents can be written as JSON
+/// lines.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{b}"),
+            // JSON has no representation for NaN or the infinities.
+            Json::Number(n) if !n.is_finite() => write!(f, "null"),
+            Json::Number(n) => write!(f, "{n}"),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Recursive descent parser over the text of a document.
+struct Parser<'a> {
+    text: &'a str,
+    current: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) if self.match_keyword("null") => Ok(Json::Null),
+            Some(_) if self.match_keyword("true") => Ok(Json::Bool(true)),
+            Some(_) if self.match_keyword("false") => Ok(Json::Bool(false)),
+            _ => Err(self.error("Expect a value.")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect('{')?;
+        let mut members = vec![];
+        self.skip_whitespace();
+        if self.match_char('}') {
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            if self.match_char('}') {
+                return Ok(Json::Object(members));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect('[')?;
+        let mut items = vec![];
+        self.skip_whitespace();
+        if self.match_char(']') {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            if self.match_char(']') {
+                return Ok(Json::Array(items));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect('"')?;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.advance() else {
+                return Err(self.error("Unterminated string."));
+            };
+            match c {
+                '"' => return Ok(value),
+                '\\' => value.push(self.escape()?),
+                c if (c as u32) < 0x20 => return Err(self.error("Unescaped control character in string.")),
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let c = match self.advance() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.hex4()?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| self.error("Invalid unicode escape."));
+                }
+                // A character outside the basic plane, written as a
+                // surrogate pair.
+                if !(self.match_char('\\') && self.match_char('u')) {
+                    return Err(self.error("Expect a low surrogate."));
+                }
+                let low = self.hex4()?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(self.error("Expect a low surrogate."));
+                }
+                let code_point = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                return char::from_u32(code_point).ok_or_else(|| self.error("Invalid unicode escape."));
+            }
+            _ => return Err(self.error("Invalid escape sequence.")),
+        };
+        Ok(c)
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self.text.get(self.current..self.current + 4)
+            .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("Expect 4 hex digits."))?;
+        self.current += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap_or_default())
+    }
+
+    /// A number as JSON spells it: an optional minus, an integer part with
+    /// no leading zeros, then an optional fraction and exponent, each with
+    /// at least one digit.
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.current;
+        self.match_char('-');
+        if !self.match_char('0') && self.digits() == 0 {
+            return Err(self.error("Expect a digit."));
+        }
+        if self.match_char('.') && self.digits() == 0 {
+            return Err(self.error("Expect a digit after '.'."));
+        }
+        if self.match_char('e') || self.match_char('E') {
+            if !self.match_char('+') {
+                self.match_char('-');
+            }
+            if self.digits() == 0 {
+                return Err(self.error("Expect a digit in the exponent."));
+            }
+        }
+        self.text[start..self.current].parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| self.error("Invalid number."))
+    }
+
+    /// Skips a run of digits, returning how many there were.
+    fn digits(&mut self) -> usize {
+        let start = self.current;
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.advance();
+        }
+        self.current - start
+    }
+
+    fn match_keyword(&mut self, keyword: &str) -> bool {
+        if self.text[self.current..].starts_with(keyword) {
+            self.current += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.advance();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        if self.match_char(expected) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expect '{expected}'.")))
+        }
+    }
+
+    fn match_char(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.current += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.current..].chars().next()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.current += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::new(&format!("{message} (at byte {})", self.current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(text: &str) -> String {
+        Json::parse(text).expect_err(&format!("{text:?} should be rejected")).message
+    }
+
+    #[test]
+    fn parses_nested_documents() {
+        let json = Json::parse(r#" { "a" : [1, {"b": null}, []], "c": {}, "d": [true, false] } "#).unwrap();
+        assert_eq!(json, Json::object(vec![
+            ("a", Json::Array(vec![Json::Number(1.0), Json::object(vec![("b", Json::Null)]), Json::Array(vec![])])),
+            ("c", Json::Object(vec![])),
+            ("d", Json::Array(vec![Json::Bool(true), Json::Bool(false)])),
+        ]));
+        assert_eq!(Json::parse(&json.to_string()), Ok(json));
+    }
+
+    #[test]
+    fn parses_string_escapes() {
+        let cases = [
+            (r#""a\"b\\c\/d""#, "a\"b\\c/d"),
+            (r#""\b\f\n\r\t""#, "\u{8}\u{c}\n\r\t"),
+            (r#""\u0041\u00e9\u20AC""#, "Aé€"),
+            (r#""\ud83d\ude00""#, "😀"),
+            (r#""λ😀""#, "λ😀"),
+        ];
+        for (text, value) in cases {
+            assert_eq!(Json::parse(text), Ok(Json::from(value)), "parsing {text}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_strings() {
+        let cases = [
+            (r#""abc"#, "Unterminated string. (at byte 4)"),
+            (r#""\x""#, "Invalid escape sequence. (at byte 3)"),
+            (r#""\u12g4""#, "Expect 4 hex digits. (at byte 3)"),
+            (r#""\ud83d""#, "Expect a low surrogate. (at byte 7)"),
+            (r#""\ud83d\u0041""#, "Expect a low surrogate. (at byte 13)"),
+            (r#""\ude00""#, "Invalid unicode escape. (at byte 7)"),
+            ("\"a\nb\"", "Unescaped control character in string. (at byte 3)"),
+        ];
+        for (text, message) in cases {
+            assert_eq!(parse_error(text), message, "parsing {text}");
+        }
+    }
+
+    #[test]
+    fn parses_numbers() {
+        let cases = [
+            ("0", 0.0),
+            ("-0", -0.0),
+            ("42", 42.0),
+            ("-1.5", -1.5),
+            ("0.25", 0.25),
+            ("1e3", 1000.0),
+            ("1E+3", 1000.0),
+            ("2.5e-1", 0.25),
+        ];
+        for (text, n) in cases {
+            assert_eq!(Json::parse(text), Ok(Json::Number(n)), "parsing {text}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_numbers() {
+        let cases = [
+            ("01", "Expect end of input. (at byte 1)"),
+            ("-01", "Expect end of input. (at byte 2)"),
+            ("1.", "Expect a digit after '.'. (at byte 2)"),
+            (".5", "Expect a value. (at byte 0)"),
+            ("-", "Expect a digit. (at byte 1)"),
+            ("+1", "Expect a value. (at byte 0)"),
+            ("1e", "Expect a digit in the exponent. (at byte 2)"),
+            ("1e+", "Expect a digit in the exponent. (at byte 3)"),
+            ("1-2", "Expect end of input. (at byte 1)"),
+        ];
+        for (text, message) in cases {
+            assert_eq!(parse_error(text), message, "parsing {text}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_structure() {
+        let cases = [
+            ("", "Expect a value. (at byte 0)"),
+            ("[1,]", "Expect a value. (at byte 3)"),
+            ("[1 2]", "Expect ','. (at byte 3)"),
+            (r#"{"a" 1}"#, "Expect ':'. (at byte 5)"),
+            (r#"{1: 2}"#, "Expect '\"'. (at byte 1)"),
+            ("nul", "Expect a value. (at byte 0)"),
+            ("true false", "Expect end of input. (at byte 5)"),
+        ];
+        for (text, message) in cases {
+            assert_eq!(parse_error(text), message, "parsing {text}");
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use std::io;
+use std::io::{IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+/// A key press, decoded from the bytes a terminal sends for it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Home,
+    End,
+    Up,
+    Down,
+    /// CTRL-U, which clears the line.
+    Clear,
+    /// CTRL-C, which abandons the input.
+    Interrupt,
+    /// CTRL-D, which ends the input on an empty line.
+    EndOfInput,
+    /// Anything else, such as function keys.
+    Ignored,
+}
+
+/// What to do once a key has been applied to the line.
+#[derive(Debug, PartialEq)]
+enum Step {
+    Continue,
+    Submit(String),
+    Interrupt,
+    EndOfInput,
+}
+
+/// Reads one line from the terminal, letting the user move around it and
+/// recall earlier lines with the up and down arrows. `history` is ordered
+/// from the oldest entry to the most recent.
+///
+/// The terminal is only put in raw mode, through `stty`, while the line is
+/// read. When stdin or stdout isn't a terminal, or `stty` is missing, lines
+/// are read as they come without any editing.
+///
+/// Returns `None` at the end of the input. An interrupted line is an error
+/// of kind `Interrupted`.
+pub fn read_line(prompt: &str, history: &[String]) -> io::Result<Option<String>> {
+    let raw_mode = match io::stdin().is_terminal() && io::stdout().is_terminal() {
+        true => RawMode::enable(),
+        false => None,
+    };
+    if raw_mode.is_none() {
+        return read_plain_line(prompt);
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let mut line = Line::new(history);
+    line.draw(&mut stdout, prompt)?;
+    loop {
+        let Some(key) = read_key(&mut stdin)? else {
+            return Ok(None);
+        };
+        match line.apply(key) {
+            Step::Continue => line.draw(&mut stdout, prompt)?,
+            Step::Submit(text) => {
+                write!(stdout, "\r\n")?;
+                return Ok(Some(text));
+            }
+            Step::Interrupt => {
+                write!(stdout, "^C\r\n")?;
+                return Err(io::Error::from(io::ErrorKind::Interrupted));
+            }
+            Step::EndOfInput => return Ok(None),
+        }
+    }
+}
+
+fn read_plain_line(prompt: &str) -> io::Result<Option<String>> {
+    print!("{prompt}");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let end = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(end);
+    Ok(Some(line))
+}
+
+/// Keeps the terminal in raw mode, without echo or line buffering, until
+/// dropped.
+struct RawMode {
+    /// The settings to restore, as printed by `stty -g`.
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Option<Self> {
+        let output = Command::new("stty").arg("-g").stdin(Stdio::inherit()).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let saved = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        let status = Command::new("stty").args(["raw", "-echo"]).stdin(Stdio::inherit()).status().ok()?;
+        status.success().then_some(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = Command::new("stty").arg(&self.saved).stdin(Stdio::inherit()).status();
+    }
+}
+
+/// Reads the bytes of one key press. Returns `None` at the end of the input.
+fn read_key(input: &mut impl Read) -> io::Result<Option<Key>> {
+    let Some(byte) = read_byte(input)? else {
+        return Ok(None);
+    };
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x05 => Key::End,
+        0x02 => Key::Left,
+        0x06 => Key::Right,
+        0x10 => Key::Up,
+        0x0e => Key::Down,
+        0x15 => Key::Clear,
+        0x03 => Key::Interrupt,
+        0x04 => Key::EndOfInput,
+        0x1b => read_escape_sequence(input)?,
+        byte if byte < 0x20 => Key::Ignored,
+        byte => read_char(input, byte)?,
+    };
+    Ok(Some(key))
+}
+
+/// Decodes the rest of an escape sequence, such as `ESC [ A` for the up
+/// arrow or `ESC [ 3 ~` for delete.
+fn read_escape_sequence(input: &mut impl Read) -> io::Result<Key> {
+    let introducer = read_byte(input)?;
+    if !matches!(introducer, Some(b'[') | Some(b'O')) {
+        return Ok(Key::Ignored);
+    }
+
+    let mut parameter = String::new();
+    loop {
+        let Some(byte) = read_byte(input)? else {
+            return Ok(Key::Ignored);
+        };
+        match byte {
+            b'0'..=b'9' | b';' => parameter.push(byte as char),
+            b'A' => return Ok(Key::Up),
+            b'B' => return Ok(Key::Down),
+            b'C' => return Ok(Key::Right),
+            b'D' => return Ok(Key::Left),
+            b'H' => return Ok(Key::Home),
+            b'F' => return Ok(Key::End),
+            b'~' => {
+                return Ok(match parameter.as_str() {
+                    "1" | "7" => Key::Home,
+                    "4" | "8" => Key::End,
+                    "3" => Key::Delete,
+                    _ => Key::Ignored,
+                })
+            }
+            _ => return Ok(Key::Ignored),
+        }
+    }
+}
+
+/// Decodes a char from its first byte and as many more as UTF-8 says
+/// follow it.
+fn read_char(input: &mut impl Read, first: u8) -> io::Result<Key> {
+    let length = match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    let mut bytes = vec![first];
+    for _ in 1..length {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => break,
+        }
+    }
+    Ok(std::str::from_utf8(&bytes).ok()
+        .and_then(|text| text.chars().next())
+        .map_or(Key::Ignored, Key::Char))
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// The line being edited, and where it is in the history.
+struct Line<'a> {
+    history: &'a [String],
+    chars: Vec<char>,
+    /// Position of the cursor in `chars`, from 0 to its length.
+    cursor: usize,
+    /// Index of the history entry shown, or the length of the history when
+    /// showing the line the user was typing.
+    recalled: usize,
+    /// The line the user was typing before recalling an entry.
+    draft: Vec<char>,
+}
+
+impl<'a> Line<'a> {
+    fn new(history: &'a [String]) -> Self {
+        Line {
+            history,
+            chars: vec![],
+            cursor: 0,
+            recalled: history.len(),
+            draft: vec![],
+        }
+    }
+
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    fn apply(&mut self, key: Key) -> Step {
+        match key {
+            Key::Char(c) => {
+                self.chars.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Enter => return Step::Submit(self.text()),
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.chars.remove(self.cursor);
+            }
+            Key::Delete if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.chars.len()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.chars.len(),
+            Key::Up if self.recalled > 0 => self.recall(self.recalled - 1),
+            Key::Down if self.recalled < self.history.len() => self.recall(self.recalled + 1),
+            Key::Clear => {
+                self.chars.clear();
+                self.cursor = 0;
+            }
+            Key::Interrupt => return Step::Interrupt,
+            Key::EndOfInput if self.chars.is_empty() => return Step::EndOfInput,
+            // Like readline, CTRL-D deletes forward on a line with text.
+            Key::EndOfInput if self.cursor < self.chars.len() => {
+                self.chars.remove(self.cursor);
+            }
+            _ => {}
+        }
+        Step::Continue
+    }
+
+    /// Shows a history entry, or the draft past the most recent one, with
+    /// the cursor at its end.
+    fn recall(&mut self, index: usize) {
+        if self.recalled == self.history.len() {
+            self.draft = self.chars.clone();
+        }
+        self.recalled = index;
+        self.chars = match self.history.get(index) {
+            Some(entry) => entry.chars().collect(),
+            None => self.draft.clone(),
+        };
+        self.cursor = self.chars.len();
+    }
+
+    /// Redraws the line in place and puts the terminal's cursor back on the
+    /// line's. Lines wider than the terminal aren't accounted for.
+    fn draw(&self, out: &mut impl Write, prompt: &str) -> io::Result<()> {
+        write!(out, "\r{prompt}{}\x1b[K", self.text())?;
+        let back = self.chars.len() - self.cursor;
+        if back > 0 {
+            write!(out, "\x1b[{back}D")?;
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(mut bytes: &[u8]) -> Vec<Key> {
+        let mut keys = vec![];
+        while let Some(key) = read_key(&mut bytes).unwrap() {
+            keys.push(key);
+        }
+        keys
+    }
+
+    /// Applies the keys to an empty line, returning its text and cursor and
+    /// the step after the last key.
+    fn edit(history: &[&str], keys: &[Key]) -> (String, usize, Step) {
+        let history: Vec<String> = history.iter().map(|entry| entry.to_string()).collect();
+        let mut line = Line::new(&history);
+        let mut step = Step::Continue;
+        for key in keys {
+            step = line.apply(*key);
+        }
+        (line.text(), line.cursor, step)
+    }
+
+    fn typed(text: &str) -> Vec<Key> {
+        text.chars().map(Key::Char).collect()
+    }
+
+    #[test]
+    fn decodes_keys() {
+        assert_eq!(keys(b"a\r"), vec![Key::Char('a'), Key::Enter]);
+        assert_eq!(keys("λ→".as_bytes()), vec![Key::Char('λ'), Key::Char('→')]);
+        assert_eq!(keys(b"\x1b[A\x1b[B\x1b[C\x1b[D"), vec![Key::Up, Key::Down, Key::Right, Key::Left]);
+        assert_eq!(keys(b"\x1bOH\x1b[F\x1b[1~\x1b[4~"), vec![Key::Home, Key::End, Key::Home, Key::End]);
+        assert_eq!(keys(b"\x1b[3~\x7f"), vec![Key::Delete, Key::Backspace]);
+        assert_eq!(keys(b"\x01\x05\x15\x03\x04"), vec![Key::Home, Key::End, Key::Clear, Key::Interrupt, Key::EndOfInput]);
+        assert_eq!(keys(b"\x1b[15~\x1b[1;5A\x07"), vec![Key::Ignored, Key::Up, Key::Ignored]);
+    }
+
+    #[test]
+    fn edits_at_the_cursor() {
+        let mut keys = typed("print 2;");
+        keys.extend([Key::Left, Key::Backspace, Key::Char('1')]);
+        assert_eq!(edit(&[], &keys), ("print 1;".to_string(), 7, Step::Continue));
+
+        keys.extend([Key::Home, Key::Delete, Key::Char('P'), Key::End, Key::Right]);
+        assert_eq!(edit(&[], &keys), ("Print 1;".to_string(), 8, Step::Continue));
+
+        keys.extend([Key::Clear, Key::Char('x'), Key::Enter]);
+        assert_eq!(edit(&[], &keys), ("x".to_string(), 1, Step::Submit("x".to_string())));
+    }
+
+    #[test]
+    fn recalls_history() {
+        let history = ["print 1;", "print 2;"];
+        assert_eq!(edit(&history, &[Key::Up]).0, "print 2;");
+        assert_eq!(edit(&history, &[Key::Up, Key::Up, Key::Up]).0, "print 1;");
+        assert_eq!(edit(&history, &[Key::Up, Key::Up, Key::Down]).0, "print 2;");
+
+        // Going past the most recent entry brings back what was typed.
+        let mut keys = typed("pri");
+        keys.extend([Key::Up, Key::Down, Key::Down]);
+        assert_eq!(edit(&history, &keys), ("pri".to_string(), 3, Step::Continue));
+        assert_eq!(edit(&[], &[Key::Up]).0, "");
+    }
+
+    #[test]
+    fn ends_only_on_an_empty_line() {
+        assert_eq!(edit(&[], &[Key::EndOfInput]).2, Step::EndOfInput);
+        let mut keys = typed("ab");
+        keys.extend([Key::Home, Key::EndOfInput]);
+        assert_eq!(edit(&[], &keys), ("b".to_string(), 0, Step::Continue));
+        assert_eq!(edit(&[], &typed("ab")).2, Step::Continue);
+        assert_eq!(edit(&[], &[Key::Char('a'), Key::Interrupt]).2, Step::Interrupt);
+    }
+}
@@ -0,0 +1,12 @@
+use std::fmt;
+use std::rc::Rc;
+use crate::interpreter::{Interpreter, RuntimeError};
+use crate::value::Value;
+
+/// Anything that can be called from Lox code. User-defined functions and
+/// natives implemented in Rust share this single call path.
+pub trait LoxCallable: fmt::Display {
+    fn arity(&self) -> usize;
+
+    fn call(self: Rc<Self>, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError>;
+}
@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use crate::interpreter::{Interpreter, RuntimeError};
+use crate::lox_callable::LoxCallable;
+use crate::lox_function::LoxFunction;
+use crate::lox_instance::LoxInstance;
+use crate::value::Value;
+
+/// A class declared in Lox code. Calling it creates a new instance.
+pub struct LoxClass {
+    pub name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<String, Rc<LoxFunction>>,
+}
+
+impl LoxClass {
+    pub fn new(name: String, superclass: Option<Rc<LoxClass>>, methods: HashMap<String, Rc<LoxFunction>>) -> Self {
+        LoxClass {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    /// Looks a method up on this class, then along the superclass chain.
+    pub fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(method.clone());
+        }
+
+        let mut class = self.superclass.clone();
+        while let Some(current) = class {
+            if let Some(method) = current.methods.get(name) {
+                return Some(method.clone());
+            }
+            class = current.superclass.clone();
+        }
+        None
+    }
+}
+
+impl LoxCallable for LoxClass {
+    fn arity(&self) -> usize {
+        match self.find_method("init") {
+            Some(initializer) => initializer.arity(),
+            None => 0,
+        }
+    }
+
+    fn call(self: Rc<Self>, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        let instance = Rc::new(RefCell::new(LoxInstance::new(self.clone())));
+        if let Some(initializer) = self.find_method("init") {
+            Rc::new(initializer.bind(instance.clone())).call(interpreter, arguments)?;
+        }
+        Ok(Value::Instance(instance))
+    }
+}
+
+impl fmt::Display for LoxClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
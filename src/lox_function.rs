@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use crate::environment::Environment;
+use crate::interpreter::{Interpreter, RuntimeError, Unwind};
+use crate::lox_callable::LoxCallable;
+use crate::lox_instance::LoxInstance;
+use crate::statement::Function;
+use crate::value::Value;
+
+/// A function declared in Lox code with `fun`, or a method of a class,
+/// together with the scope it was declared in.
+pub struct LoxFunction {
+    declaration: Function,
+    closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(declaration: Function, closure: Rc<RefCell<Environment>>, is_initializer: bool) -> Self {
+        LoxFunction {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// Returns a copy of this method whose scope binds `this` to `instance`.
+    pub fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> LoxFunction {
+        let mut environment = Environment::new_enclosed(self.closure.clone());
+        environment.define("this", Value::Instance(instance));
+        LoxFunction::new(self.declaration.clone(), Rc::new(RefCell::new(environment)), self.is_initializer)
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+
+    fn call(self: Rc<Self>, interpreter: &mut Interpreter, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        let mut environment = Environment::new_enclosed(self.closure.clone());
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.define(&param.lexeme, argument);
+        }
+
+        let value = match interpreter.execute_block(&self.declaration.body, environment) {
+            Ok(()) => Value::Nil,
+            Err(Unwind::Return(value)) => value,
+            Err(Unwind::Error(error)) => return Err(error),
+        };
+
+        // An initializer always hands back the instance, even on `return;`.
+        if self.is_initializer {
+            return Ok(self.closure.borrow().get_local("this").unwrap_or(Value::Nil));
+        }
+        Ok(value)
+    }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<fn {}>", self.declaration.name.lexeme)
+    }
+}
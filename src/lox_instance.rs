@@ -0,0 +1,47 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use crate::interpreter::RuntimeError;
+use crate::lox_class::LoxClass;
+use crate::token::Token;
+use crate::value::Value;
+
+/// An instance of a Lox class, holding its own fields.
+pub struct LoxInstance {
+    class: Rc<LoxClass>,
+    fields: HashMap<String, Value>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        LoxInstance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Looks a property up on the instance. Fields shadow methods, and
+    /// methods come back bound to the instance so `this` works inside them.
+    pub fn get(instance: &Rc<RefCell<LoxInstance>>, name: &Token) -> Result<Value, RuntimeError> {
+        if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(value.clone());
+        }
+
+        let method = instance.borrow().class.find_method(&name.lexeme);
+        match method {
+            Some(method) => Ok(Value::Callable(Rc::new(method.bind(instance.clone())))),
+            None => Err(RuntimeError::new(name, format!("Undefined property '{}'.", name.lexeme).as_str())),
+        }
+    }
+
+    pub fn set(&mut self, name: &Token, value: Value) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
+
+impl fmt::Display for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
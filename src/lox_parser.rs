@@ -1,21 +1,47 @@
-use std::cmp::PartialEq;
-use crate::expression::{Expr, Literal};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::diagnostic::{Diagnostic, SYNTAX_ERROR};
+use crate::expression;
+use crate::expression::{Assign, Binary, Call, Expr, Get, Grouping, Literal, Logical, Set, Unary, Variable};
+use crate::statement;
+use crate::span::Span;
+use crate::statement::{Block, Expression, Function, Stmt};
 use crate::token::Token;
 use crate::token_type::TokenType;
-use crate::token_type::TokenType::EOF;
+use crate::value::LiteralValue;
+use crate::token_type::TokenType::{And, Bang, BangEqual, Class, Comma, Dot, Else, EOF, Equal, EqualEqual, False, For, Fun, Greater, GreaterEqual, Identifier, If, LeftBrace, LeftParen, Less, LessEqual, LoxString, Minus, Nil, Number, Or, Plus, Print, Return, RightBrace, RightParen, SemiColon, Slash, Star, Super, This, True, Var, While};
 
+/// Raised when the parser hits a syntax error. The error itself has already
+/// been recorded as a diagnostic when this is returned, so callers only need
+/// to unwind.
+#[derive(Debug)]
+pub struct ParseError;
+
+/// Upper bound on the number of parameters of a function, and on the
+/// number of arguments in a call.
+const MAX_ARGUMENTS: usize = 255;
+
+/// Upper bound on how deep statements and expressions can nest. Each level
+/// takes tens of kilobytes of stack in a debug build, so this keeps well
+/// within `interpreter::STACK_SIZE`.
+const MAX_NESTING: usize = 500;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Hands out ids for the expression nodes the resolver needs to identify.
+/// They stay unique across parses, so one interpreter can keep running
+/// code from several sources, as the prompt does.
+pub fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Default)]
 pub struct LoxParser {
     tokens: Vec<Token>,
     current: usize,
-}
-
-impl Default for LoxParser {
-    fn default() -> Self {
-        LoxParser {
-            tokens: vec![],
-            current: 0,
-        }
-    }
+    diagnostics: Vec<Diagnostic>,
+    /// Number of nested rules being parsed, see `nested`.
+    depth: usize,
 }
 
 impl LoxParser {
@@ -26,15 +52,462 @@ impl LoxParser {
         }
     }
 
-    fn expression() -> Expr {
-        LoxParser::equality()
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements: Vec<Stmt> = vec![];
+        while !self.is_at_end() {
+            match self.declaration() {
+                Ok(statement) => statements.push(statement),
+                Err(_) => {
+                    // Skip to the next statement so that every independent
+                    // syntax error gets reported in a single run.
+                    self.synchronize();
+                }
+            }
+        }
+        // Some errors, like an invalid assignment target, are recorded
+        // without unwinding, so look at the diagnostics rather than the
+        // results.
+        if !self.diagnostics.is_empty() {
+            return Err(ParseError);
+        }
+        Ok(statements)
+    }
+
+    /// Parses the tokens as a single expression with nothing after it, as
+    /// the prompt does to echo the value of what is typed. Returns `None` if
+    /// they are anything else.
+    pub fn parse_expression(&mut self) -> Option<Expr> {
+        let expression = self.expression().ok()?;
+        if !self.is_at_end() || !self.diagnostics.is_empty() {
+            return None;
+        }
+        Some(expression)
+    }
+
+    /// Syntax errors found by `parse` or `parse_expression`.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        if self.match_token(vec![Class]) {
+            return self.class_declaration();
+        }
+        if self.match_token(vec![Fun]) {
+            let start = self.previous().span;
+            return Ok(Stmt::Function(self.function("function", start)?));
+        }
+        if self.match_token(vec![Var]) {
+            return self.var_declaration();
+        }
+        self.statement()
+    }
+
+    fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
+        let name = self.consume(Identifier, "Expect class name.")?;
+
+        let superclass = if self.match_token(vec![Less]) {
+            let superclass_name = self.consume(Identifier, "Expect superclass name.")?;
+            let span = superclass_name.span;
+            Some(Variable::new(superclass_name, next_id(), span))
+        } else {
+            None
+        };
+
+        self.consume(LeftBrace, "Expect '{' before class body.")?;
+
+        let mut methods: Vec<Function> = vec![];
+        while !self.check(&RightBrace) && !self.is_at_end() {
+            let start = self.peek().span;
+            methods.push(self.function("method", start)?);
+        }
+
+        self.consume(RightBrace, "Expect '}' after class body.")?;
+        Ok(Stmt::Class(statement::Class::new(name, superclass, methods, self.span_from(start))))
+    }
+
+    /// Parses the name, parameters and body of a function or method. `kind`
+    /// is only used in error messages, `start` is where the declaration began.
+    fn function(&mut self, kind: &str, start: Span) -> Result<Function, ParseError> {
+        let name = self.consume(Identifier, format!("Expect {kind} name.").as_str())?;
+        self.consume(LeftParen, format!("Expect '(' after {kind} name.").as_str())?;
+
+        let mut params: Vec<Token> = vec![];
+        if !self.check(&RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.error(&self.peek(), "Can't have more than 255 parameters.");
+                }
+                params.push(self.consume(Identifier, "Expect parameter name.")?);
+                if !self.match_token(vec![Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(LeftBrace, format!("Expect '{{' before {kind} body.").as_str())?;
+        // Function bodies nest like blocks, without going through `statement`.
+        let body = self.nested(Self::block)?;
+        Ok(Function::new(name, params, Rc::new(body), self.span_from(start)))
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
+        let name = self.consume(Identifier, "Expect variable name.")?;
+
+        let initializer = if self.match_token(vec![Equal]) {
+            Some(Box::new(self.expression()?))
+        } else {
+            None
+        };
+
+        self.consume(SemiColon, "Expect ';' after variable declaration.")?;
+        Ok(Stmt::Var(statement::Var::new(name, initializer, self.span_from(start))))
+    }
+
+    /// Statements can nest inside blocks and the bodies of `if`, `while`
+    /// and `for`, so they count towards the nesting limit.
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
+        self.nested(|parser| {
+            if parser.match_token(vec![For]) {
+                return parser.for_statement();
+            }
+            if parser.match_token(vec![If]) {
+                return parser.if_statement();
+            }
+            if parser.match_token(vec![Print]) {
+                return parser.print_statement();
+            }
+            if parser.match_token(vec![Return]) {
+                return parser.return_statement();
+            }
+            if parser.match_token(vec![While]) {
+                return parser.while_statement();
+            }
+            if parser.match_token(vec![LeftBrace]) {
+                let start = parser.previous().span;
+                let statements = parser.block()?;
+                return Ok(Stmt::Block(Block::new(statements, parser.span_from(start))));
+            }
+            parser.expression_statement()
+        })
+    }
+
+    /// A `for` loop has no node of its own: it is desugared into a `while`
+    /// loop, wrapped in blocks for the initializer and the increment.
+    fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
+        self.consume(LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.match_token(vec![SemiColon]) {
+            None
+        } else if self.match_token(vec![Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = if !self.check(&SemiColon) {
+            self.expression()?
+        } else {
+            Expr::Literal(Literal::new(LiteralValue::Bool(true), self.peek().span))
+        };
+        self.consume(SemiColon, "Expect ';' after loop condition.")?;
+
+        let increment = if !self.check(&RightParen) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(RightParen, "Expect ')' after for clauses.")?;
+
+        let mut body = self.statement()?;
+        let span = self.span_from(start);
+
+        if let Some(increment) = increment {
+            let increment_span = increment.span();
+            body = Stmt::Block(Block::new(vec![
+                body,
+                Stmt::Expression(Expression::new(Box::new(increment), increment_span)),
+            ], span));
+        }
+
+        body = Stmt::While(statement::While::new(Box::new(condition), Box::new(body), span));
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(Block::new(vec![initializer, body], span));
+        }
+
+        Ok(body)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
+        self.consume(LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = self.statement()?;
+        let else_branch = if self.match_token(vec![Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If(statement::If::new(Box::new(condition), Box::new(then_branch), else_branch, self.span_from(start))))
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let value = if !self.check(&SemiColon) {
+            Some(Box::new(self.expression()?))
+        } else {
+            None
+        };
+
+        self.consume(SemiColon, "Expect ';' after return value.")?;
+        Ok(Stmt::Return(statement::Return::new(keyword.clone(), value, self.span_from(keyword.span))))
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
+        self.consume(LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(RightParen, "Expect ')' after condition.")?;
+        let body = self.statement()?;
+
+        Ok(Stmt::While(statement::While::new(Box::new(condition), Box::new(body), self.span_from(start))))
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements: Vec<Stmt> = vec![];
+
+        while !self.check(&RightBrace) && !self.is_at_end() {
+            statements.push(self.declaration()?);
+        }
+
+        self.consume(RightBrace, "Expect '}' after block.")?;
+        Ok(statements)
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span;
+        let value = self.expression()?;
+        self.consume(SemiColon, "Expect ';' after value.")?;
+        Ok(Stmt::Print(statement::Print::new(Box::new(value), self.span_from(start))))
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
+        let expr = self.expression()?;
+        self.consume(SemiColon, "Expect ';' after expression.")?;
+        let span = expr.span().to(self.previous().span);
+        Ok(Stmt::Expression(Expression::new(Box::new(expr), span)))
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParseError> {
+        self.nested(Self::assignment)
+    }
+
+    fn assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.or()?;
+
+        if self.match_token(vec![Equal]) {
+            let equals = self.previous();
+            let value = self.nested(Self::assignment)?;
+
+            let span = expr.span().to(value.span());
+            match expr {
+                Expr::Variable(variable) => {
+                    return Ok(Expr::Assign(Assign::new(variable.name, Box::new(value), next_id(), span)));
+                }
+                Expr::Get(get) => {
+                    return Ok(Expr::Set(Set::new(get.object, get.name, Box::new(value), span)));
+                }
+                _ => {}
+            }
+
+            // Report without unwinding: the parser is not in a confused state.
+            self.error(&equals, "Invalid assignment target.");
+        }
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.and()?;
+
+        while self.match_token(vec![Or]) {
+            let operator = self.previous();
+            let right = self.and()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Logical(Logical::new(Box::new(expr), operator, Box::new(right), span));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.equality()?;
+
+        while self.match_token(vec![And]) {
+            let operator = self.previous();
+            let right = self.equality()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Logical(Logical::new(Box::new(expr), operator, Box::new(right), span));
+        }
+
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.comparison()?;
+
+        while self.match_token(vec![BangEqual, EqualEqual]) {
+            let operator = self.previous();
+            let right = self.comparison()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Binary(Binary::new(Box::new(expr), operator, Box::new(right), span));
+        }
+
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.term()?;
+
+        while self.match_token(vec![Greater, GreaterEqual, Less, LessEqual]) {
+            let operator = self.previous();
+            let right = self.term()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Binary(Binary::new(Box::new(expr), operator, Box::new(right), span));
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.factor()?;
+
+        while self.match_token(vec![Minus, Plus]) {
+            let operator = self.previous();
+            let right = self.factor()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Binary(Binary::new(Box::new(expr), operator, Box::new(right), span));
+        }
+
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = self.unary()?;
+
+        while self.match_token(vec![Slash, Star]) {
+            let operator = self.previous();
+            let right = self.unary()?;
+            let span = expr.span().to(right.span());
+            expr = Expr::Binary(Binary::new(Box::new(expr), operator, Box::new(right), span));
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.match_token(vec![Bang, Minus]) {
+            let operator = self.previous();
+            let right = self.nested(Self::unary)?;
+            let span = operator.span.to(right.span());
+            return Ok(Expr::Unary(Unary::new(operator, Box::new(right), span)));
+        }
+
+        self.call()
     }
 
-    fn equality() -> Expr {
-        let expr: Expr = LoxParser::comparison();
-        //
+    fn call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.match_token(vec![LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_token(vec![Dot]) {
+                let name = self.consume(Identifier, "Expect property name after '.'.")?;
+                let span = expr.span().to(name.span);
+                expr = Expr::Get(Get::new(Box::new(expr), name, span));
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let mut arguments: Vec<Expr> = vec![];
+        if !self.check(&RightParen) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    self.error(&self.peek(), "Can't have more than 255 arguments.");
+                }
+                arguments.push(self.expression()?);
+                if !self.match_token(vec![Comma]) {
+                    break;
+                }
+            }
+        }
+
+        let paren = self.consume(RightParen, "Expect ')' after arguments.")?;
+        let span = callee.span().to(paren.span);
+        Ok(Expr::Call(Call::new(Box::new(callee), paren, arguments, span)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let token = self.peek();
+        let value = match token.token_type {
+            False => Some(LiteralValue::Bool(false)),
+            True => Some(LiteralValue::Bool(true)),
+            Nil => Some(LiteralValue::Nil),
+            Number(_) | LoxString(_) => token.literal.clone(),
+            _ => None,
+        };
+        if let Some(value) = value {
+            self.advance();
+            return Ok(Expr::Literal(Literal::new(value, token.span)));
+        }
+
+        if self.match_token(vec![Super]) {
+            let keyword = self.previous();
+            self.consume(Dot, "Expect '.' after 'super'.")?;
+            let method = self.consume(Identifier, "Expect superclass method name.")?;
+            let span = keyword.span.to(method.span);
+            return Ok(Expr::Super(expression::Super::new(keyword, method, next_id(), span)));
+        }
+
+        if self.match_token(vec![This]) {
+            let keyword = self.previous();
+            let span = keyword.span;
+            return Ok(Expr::This(expression::This::new(keyword, next_id(), span)));
+        }
+
+        if self.match_token(vec![Identifier]) {
+            let name = self.previous();
+            let span = name.span;
+            return Ok(Expr::Variable(Variable::new(name, next_id(), span)));
+        }
+
+        if self.match_token(vec![LeftParen]) {
+            let start = self.previous().span;
+            let expr = self.expression()?;
+            self.consume(RightParen, "Expect ')' after expression.")?;
+            return Ok(Expr::Grouping(Grouping::new(Box::new(expr), self.span_from(start))));
+        }
+
+        Err(self.error(&token, "Expect expression."))
+    }
 
-        expr
+    /// The span from `start` to the end of the last consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span)
     }
 
     fn match_token(&mut self, types: Vec<TokenType>) -> bool {
@@ -47,6 +520,60 @@ impl LoxParser {
         false
     }
 
+    /// Runs a rule that may recurse into itself, failing once the nesting is
+    /// too deep to parse without running out of stack.
+    fn nested<T>(&mut self, rule: impl FnOnce(&mut Self) -> Result<T, ParseError>) -> Result<T, ParseError> {
+        if self.depth >= MAX_NESTING {
+            let error = self.error(&self.peek(), "Too much nesting.");
+            // There is no statement boundary to resume at inside the
+            // nesting, so give up on the rest of the input.
+            self.current = self.tokens.len() - 1;
+            return Err(error);
+        }
+        self.depth += 1;
+        let result = rule(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn consume(&mut self, token_type: TokenType, message: &str) -> Result<Token, ParseError> {
+        if self.check(&token_type) {
+            return Ok(self.advance());
+        }
+        Err(self.error(&self.peek(), message))
+    }
+
+    /// Records a syntax error at a token, naming the token the way jlox does
+    /// so the message still makes sense without the source excerpt.
+    fn error(&mut self, token: &Token, message: &str) -> ParseError {
+        let location = match &token.token_type {
+            EOF => "at end".to_string(),
+            _ => format!("at '{}'", token.lexeme),
+        };
+        let message = format!("{message} ({location})");
+        self.diagnostics.push(Diagnostic::error(SYNTAX_ERROR, &message, token.span));
+        ParseError
+    }
+
+    /// Discards tokens until the start of the next statement, so that parsing
+    /// can resume after an error without reporting cascaded ones.
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().token_type == SemiColon {
+                return;
+            }
+
+            match self.peek().token_type {
+                Class | Fun | Var | For | If | While | Print | Return => return,
+                _ => {}
+            }
+
+            self.advance();
+        }
+    }
+
     fn check(&self, token_type: &TokenType) -> bool {
         if self.is_at_end() {
             return false;
@@ -59,11 +586,20 @@ impl LoxParser {
     }
 
     fn peek(&self) -> Token {
-        self.tokens.get(self.current).unwrap().clone()
+        self.token_at(self.current)
     }
 
     fn previous(&self) -> Token {
-        self.tokens.get(self.current - 1).unwrap().clone()
+        self.token_at(self.current.saturating_sub(1))
+    }
+
+    fn token_at(&self, index: usize) -> Token {
+        // The scanner always terminates the stream with EOF, but a malformed
+        // stream must not make the parser panic either.
+        match self.tokens.get(index).or(self.tokens.last()) {
+            Some(token) => token.clone(),
+            None => Token::new(EOF, "".to_string(), None, Span::new(0, 0, 1, 1)),
+        }
     }
 
     fn advance(&mut self) -> Token {
@@ -72,8 +608,118 @@ impl LoxParser {
         }
         self.previous()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_printer::AstPrinter;
+    use crate::test_support::{parse, scan};
+
+    /// The syntax tree of a single statement, as printed by `AstPrinter`.
+    fn tree(source: &str) -> String {
+        AstPrinter::new().print(&parse(source)).trim_end().to_string()
+    }
+
+    /// The line and message of every syntax error in the source.
+    fn syntax_errors(source: &str) -> Vec<(usize, String)> {
+        let mut parser = LoxParser::new(scan(source));
+        let result = parser.parse();
+        assert_eq!(result.is_err(), !parser.diagnostics().is_empty(), "result of {source:?}");
+        parser.diagnostics().iter()
+            .map(|diagnostic| (diagnostic.span.line, diagnostic.message.clone()))
+            .collect()
+    }
 
-    fn comparison() -> Expr {
-        Expr::Literal(Literal::new(Some("123".to_string()))) // FIXME:
+    #[test]
+    fn reports_every_independent_error() {
+        let errors = syntax_errors("print 1 +;\nvar = 3;\nprint (1;\nprint");
+        assert_eq!(errors, vec![
+            (1, "Expect expression. (at ';')".to_string()),
+            (2, "Expect variable name. (at '=')".to_string()),
+            (3, "Expect ')' after expression. (at ';')".to_string()),
+            (4, "Expect expression. (at end)".to_string()),
+        ]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn resumes_at_statement_boundaries() {
+        let cases = [
+            ("print 1;", vec![]),
+            ("print 1 print 2;", vec![1]),
+            ("var a = ;\nprint a;\nfun (x) {}", vec![1, 3]),
+            ("a + b = c;\nprint 1 2;", vec![1, 2]),
+            ("class { }\nwhile (true print 1;", vec![1, 2]),
+        ];
+        for (source, lines) in cases {
+            let errors: Vec<usize> = syntax_errors(source).into_iter().map(|(line, _)| line).collect();
+            assert_eq!(errors, lines, "errors in {source:?}");
+        }
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let cases = [
+            format!("print {}1{};", "(".repeat(200), ")".repeat(200)),
+            format!("print {}1{};", "(".repeat(200_000), ")".repeat(200_000)),
+            format!("print {}1;", "-".repeat(10_000)),
+            format!("{}a = 1;", "a = ".repeat(10_000)),
+            format!("{}print 1;{}", "{ if (true) ".repeat(10_000), "}".repeat(10_000)),
+            format!("{}{}", "fun f() { ".repeat(10_000), "}".repeat(10_000)),
+        ];
+        // Test threads get a small stack, so run on one like rlox's.
+        let mut errors = std::thread::Builder::new()
+            .stack_size(crate::interpreter::STACK_SIZE)
+            .spawn(move || cases.iter().map(|source| syntax_errors(source)).collect::<Vec<_>>())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(errors.remove(0), vec![]);
+        for errors in errors {
+            assert_eq!(errors.len(), 1, "{errors:?}");
+            assert!(errors[0].1.starts_with("Too much nesting."), "{errors:?}");
+        }
+    }
+
+    #[test]
+    fn parses_operators_by_precedence() {
+        let cases = [
+            ("1 + 2 * 3;", "(; (+ 1 (* 2 3)))"),
+            ("1 * 2 + 3;", "(; (+ (* 1 2) 3))"),
+            ("(1 + 2) * 3;", "(; (* (Group (+ 1 2)) 3))"),
+            ("-1 * 2;", "(; (* (- 1) 2))"),
+            ("!a == b;", "(; (== (! a) b))"),
+            ("1 < 2 == 3 > 4;", "(; (== (< 1 2) (> 3 4)))"),
+            ("1 + 2 <= 3 - 4;", "(; (<= (+ 1 2) (- 3 4)))"),
+            ("a or b and c;", "(; (or a (and b c)))"),
+            ("a and b == c;", "(; (and a (== b c)))"),
+            ("a = b or c;", "(; (= a (or b c)))"),
+            ("-a.b();", "(; (- (call (. b a))))"),
+            ("true != nil;", "(; (!= true nil))"),
+            (r#""a" + "b";"#, r#"(; (+ "a" "b"))"#),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(tree(source), expected, "parsing {source:?}");
+        }
+    }
+
+    #[test]
+    fn parses_operators_by_associativity() {
+        let cases = [
+            ("1 - 2 - 3;", "(; (- (- 1 2) 3))"),
+            ("8 / 4 / 2;", "(; (/ (/ 8 4) 2))"),
+            ("1 < 2 < 3;", "(; (< (< 1 2) 3))"),
+            ("a == b != c;", "(; (!= (== a b) c))"),
+            ("a or b or c;", "(; (or (or a b) c))"),
+            ("a and b and c;", "(; (and (and a b) c))"),
+            ("!!a;", "(; (! (! a)))"),
+            ("- -1;", "(; (- (- 1)))"),
+            ("a = b = c;", "(; (= a (= b c)))"),
+            ("a.b.c = d;", "(; (= . c (. b a) d))"),
+            ("f(1)(2);", "(; (call (call f 1) 2))"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(tree(source), expected, "parsing {source:?}");
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 use std::string::ToString;
-use crate::error;
+use crate::diagnostic::{Diagnostic, SCAN_ERROR};
 use crate::scanner::Scanner;
+use crate::span::Span;
 use crate::token::Token;
 use crate::token_type::TokenType;
+use crate::value::LiteralValue;
 use crate::token_type::TokenType::{And, Bang, BangEqual, Class, Comma, Dot, Else, EOF, Equal, EqualEqual, False, For, Fun, Greater, GreaterEqual, Identifier, If, LeftBrace, LeftParen, Less, LessEqual, LoxString, Minus, Nil, Number, Or, Plus, Print, Return, RightBrace, RightParen, SemiColon, Slash, Star, Super, This, True, Var, While};
 
 thread_local!(static KEYWORDS: HashMap<&'static str, TokenType> = HashMap::from([
@@ -27,20 +29,34 @@ thread_local!(static KEYWORDS: HashMap<&'static str, TokenType> = HashMap::from(
 
 pub struct LoxScanner<'a> {
     pub(crate) source: &'a[u8],
+    /// `source` decoded as UTF-8. Empty when `source` is not valid UTF-8.
+    /// Offsets into it are byte offsets, the same as into `source`.
+    text: &'a str,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    /// Column of the char at `current`, counted in chars from 1.
+    column: usize,
+    /// Position where the token being scanned starts.
+    start_line: usize,
+    start_column: usize,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl Default for LoxScanner<'_> {
     fn default() -> Self {
         LoxScanner {
             source: &[],
+            text: "",
             tokens: vec![],
             start: 0,
             current: 0,
-            line: 1
+            line: 1,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
+            diagnostics: vec![],
         }
     }
 }
@@ -49,12 +65,58 @@ impl <'a> LoxScanner<'a> {
     pub fn new(source: &'a [u8]) -> Self {
         LoxScanner {
             source,
+            text: std::str::from_utf8(source).unwrap_or_default(),
             ..Default::default()
         }
     }
 
+    /// Errors found by `scan_tokens`.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Reports the first invalid UTF-8 sequence of every line in the source.
+    /// Returns false if there was any.
+    fn check_utf8(&mut self) -> bool {
+        if self.text.len() == self.source.len() {
+            return true;
+        }
+
+        let mut offset = 0;
+        let mut line = 1;
+        let mut column = 1;
+        let mut reported_line = 0;
+        for chunk in self.source.utf8_chunks() {
+            let valid = chunk.valid();
+            match valid.rfind('\n') {
+                Some(newline) => {
+                    line += valid.matches('\n').count();
+                    column = valid[newline + 1..].chars().count() + 1;
+                }
+                None => column += valid.chars().count(),
+            }
+            offset += valid.len();
+
+            let invalid = chunk.invalid();
+            if !invalid.is_empty() && line != reported_line {
+                let span = Span::new(offset, offset + invalid.len(), line, column);
+                self.diagnostics.push(Diagnostic::error(SCAN_ERROR, "Invalid UTF-8 sequence.", span));
+                reported_line = line;
+            }
+            offset += invalid.len();
+            column += 1;
+        }
+        false
+    }
+
+    /// Reports an error on the token being scanned.
+    fn error(&mut self, message: &str) {
+        let span = Span::new(self.start, self.current, self.start_line, self.start_column);
+        self.diagnostics.push(Diagnostic::error(SCAN_ERROR, message, span));
+    }
+
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len() - 1
+        self.current >= self.source.len()
     }
 
     fn scan_token(&mut self) {
@@ -81,111 +143,336 @@ impl <'a> LoxScanner<'a> {
                     self.advance();
                 }
                 None
+            } else if self.match_next('*') {
+                self.block_comment();
+                None
             } else { Some(Slash) },
             ' ' => None,
             '\r' => None,
             '\t' => None,
             '\n' => {
-                self.line+=1;
+                self.new_line();
                 None
             },
             '"' => self.string(),
             c => {
-                let res = if is_digit(c) {
+                if is_digit(c) {
                     self.number()
                 } else if is_alpha(c) {
                     self.identifier()
                 } else {
-                    error(self.line, format!("Unexpected character {}", c).as_str());
+                    self.error("Unexpected character.");
                     None
-                };
-                res
+                }
             }
         };
 
-        match token {
-            Some(t) => self.add_token(t),
-            None => {}
+        if let Some(t) = token {
+            self.add_token(t)
         }
     }
 
     fn add_token(&mut self, token_type: TokenType) {
-        self.add_full_token(token_type, None)
+        let literal = match &token_type {
+            LoxString(value) => Some(LiteralValue::Str(value.clone())),
+            Number(value) => Some(LiteralValue::Number(*value)),
+            _ => None,
+        };
+        self.add_full_token(token_type, literal)
+    }
+
+    fn add_full_token(&mut self, token_type: TokenType, literal: Option<LiteralValue>) {
+        let lexeme: String = self.text[self.start..self.current].to_string();
+        let span = Span::new(self.start, self.current, self.start_line, self.start_column);
+        self.tokens.push(Token::new(token_type, lexeme, literal, span))
+    }
+
+    /// Called once a newline char has been consumed.
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.column = 1;
     }
 
-    fn add_full_token(&mut self, token_type: TokenType, literal: Option<String>) {
-        let lexeme_slice: &[u8] = self.source[self.start..self.current].as_ref();
-        let lexeme: String = std::str::from_utf8(lexeme_slice).expect("Invalid utf8 sequence").to_string();
-        self.tokens.push(Token::new(token_type, lexeme, literal.unwrap_or("".to_string()), self.line))
+    fn char_at(&self, index: usize) -> char {
+        self.text[index..].chars().next().unwrap_or('\0')
     }
 
     fn advance(&mut self) -> char {
-        let res = self.source[self.current];
-        self.current += 1;
-        res as char
+        let res = self.char_at(self.current);
+        self.current += res.len_utf8();
+        self.column += 1;
+        res
     }
 
     fn match_next(&mut self, next_expected_char: char) -> bool {
-        if self.is_at_end() {
-            false
-        } else if self.source[self.current] as char != next_expected_char {
+        if self.is_at_end() || self.char_at(self.current) != next_expected_char {
             false
         } else {
-            self.current += 1;
+            self.current += next_expected_char.len_utf8();
+            self.column += 1;
             true
         }
     }
 
     fn peek(&mut self) -> char {
         if self.is_at_end() {
-            return '\0';
+            '\0'
         } else {
-            self.source[self.current] as char
+            self.char_at(self.current)
         }
     }
 
     fn peek_next(&mut self) -> char {
-        if self.current+1 >= self.source.len() - 1 {
+        if self.is_at_end() {
+            return '\0';
+        }
+        let next = self.current + self.char_at(self.current).len_utf8();
+        if next >= self.source.len() {
             return '\0';
         }
-        self.source[self.current+1] as char
+        self.char_at(next)
     }
 
     fn string(&mut self) -> Option<TokenType> {
+        // The lexeme keeps the escapes as written, the payload has them decoded.
+        let mut value = String::new();
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' { self.line += 1; }
-            self.advance();
+            match self.advance() {
+                '\n' => {
+                    self.new_line();
+                    value.push('\n');
+                }
+                '\\' => {
+                    if let Some(c) = self.escape() {
+                        value.push(c);
+                    }
+                }
+                c => value.push(c),
+            }
         }
 
         if self.is_at_end() {
-            error(self.line, "Unterminated string");
+            // Point at the opening quote rather than at the rest of the file.
+            let span = Span::new(self.start, self.start + 1, self.start_line, self.start_column);
+            self.diagnostics.push(Diagnostic::error(SCAN_ERROR, "Unterminated string.", span));
             return None
         }
 
         self.advance();
-        let value: String = std::str::from_utf8(&self.source[self.start+1..self.current-1]).expect("Invalid utf8 sequence").to_string();
         Some(LoxString(value))
     }
 
-    fn number(&mut self) -> Option<TokenType> {
-        while is_digit(self.peek()) {
+    /// Decodes the escape sequence following a backslash in a string.
+    /// Returns `None` if it is invalid, after reporting it.
+    fn escape(&mut self) -> Option<char> {
+        if self.is_at_end() {
+            // Reported as an unterminated string.
+            return None;
+        }
+
+        let start = self.current - 1;
+        let line = self.line;
+        let column = self.column - 1;
+        let c = match self.advance() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '\\' => '\\',
+            '"' => '"',
+            '0' => '\0',
+            'u' => return self.unicode_escape(start, line, column),
+            c => {
+                let message = format!("Unknown escape sequence '\\{}'.", c.escape_default());
+                self.escape_error(&message, start, line, column);
+                if c == '\n' {
+                    self.new_line();
+                }
+                return None;
+            }
+        };
+        Some(c)
+    }
+
+    /// Decodes the `{XXXX}` part of a `\u{XXXX}` escape, with one to six hex
+    /// digits.
+    fn unicode_escape(&mut self, start: usize, line: usize, column: usize) -> Option<char> {
+        if !self.match_next('{') {
+            self.escape_error("Expect '{' after '\\u'.", start, line, column);
+            return None;
+        }
+
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
             self.advance();
         }
+        let digits = &self.text[digits_start..self.current];
+        if digits.is_empty() || digits.len() > 6 {
+            self.escape_error("Expect 1 to 6 hex digits in unicode escape.", start, line, column);
+            return None;
+        }
+        let code_point = u32::from_str_radix(digits, 16).ok();
+
+        if !self.match_next('}') {
+            self.escape_error("Expect '}' after unicode escape.", start, line, column);
+            return None;
+        }
+
+        let c = code_point.and_then(char::from_u32);
+        if c.is_none() {
+            self.escape_error("Invalid unicode code point.", start, line, column);
+        }
+        c
+    }
+
+    /// Reports an error on an escape sequence, from its backslash up to what
+    /// has been consumed of it.
+    fn escape_error(&mut self, message: &str, start: usize, line: usize, column: usize) {
+        let span = Span::new(start, self.current, line, column);
+        self.diagnostics.push(Diagnostic::error(SCAN_ERROR, message, span));
+    }
+
+    /// Skips a `/* ... */` comment whose opening has been consumed. Block
+    /// comments nest, so that they can be used to disable code that already
+    /// contains some.
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                // Point at the opening `/*` rather than at the rest of the file.
+                let span = Span::new(self.start, self.start + 2, self.start_line, self.start_column);
+                self.diagnostics.push(Diagnostic::error(SCAN_ERROR, "Unterminated block comment.", span));
+                return;
+            }
+
+            if self.peek() == '/' && self.peek_next() == '*' {
+                self.advance();
+                self.advance();
+                depth += 1;
+            } else if self.peek() == '*' && self.peek_next() == '/' {
+                self.advance();
+                self.advance();
+                depth -= 1;
+            } else if self.advance() == '\n' {
+                self.new_line();
+            }
+        }
+    }
+
+    /// Scans a number literal: decimal with an optional fraction and
+    /// exponent, or hex (`0xFF`) and binary (`0b1010`) integers. Digits may be
+    /// separated by single underscores. A malformed number is reported but
+    /// still produces a token, so the parser doesn't pile up errors on it.
+    fn number(&mut self) -> Option<TokenType> {
+        if self.text[self.start..].starts_with('0') {
+            match self.peek() {
+                'x' | 'X' => return Some(self.radix_number(16, "hex")),
+                'b' | 'B' => return Some(self.radix_number(2, "binary")),
+                _ => {}
+            }
+        }
+
+        self.digits();
         if self.peek() == '.' && is_digit(self.peek_next()) {
             self.advance();
+            self.digits();
         }
-        while is_digit(self.peek()) {
+        if matches!(self.peek(), 'e' | 'E') && matches!(self.peek_next(), '0'..='9' | '+' | '-' | '_') {
+            let exponent = self.current;
             self.advance();
+            if matches!(self.peek(), '+' | '-') {
+                self.advance();
+            }
+            if !is_digit(self.peek()) && self.peek() != '_' {
+                self.number_error("Expect digits in exponent.", exponent, self.current);
+                return Some(Number(0.0));
+            }
+            self.digits();
+        }
+
+        if !self.check_underscores(self.start, 10) {
+            return Some(Number(0.0));
         }
-        let value: f32 = std::str::from_utf8(&self.source[self.start..self.current]).expect("Invalid utf8 sequence").to_string().parse::<f32>().unwrap();
+        let value = self.text[self.start..self.current].replace('_', "").parse::<f64>().unwrap_or(0.0);
         Some(Number(value))
     }
 
+    /// Scans the rest of a hex or binary literal, once its leading `0` has
+    /// been consumed.
+    fn radix_number(&mut self, radix: u32, name: &str) -> TokenType {
+        let prefix = self.advance();
+        let digits_start = self.current;
+        // Take any letter too, so that `0b102` or `0xFG` get reported
+        // instead of being split into several tokens.
+        while is_alpha_numeric(self.peek()) {
+            self.advance();
+        }
+
+        let digits = &self.text[digits_start..self.current];
+        if digits.is_empty() {
+            let message = format!("Expect {name} digits after '0{prefix}'.");
+            self.number_error(&message, self.start, self.current);
+            return Number(0.0);
+        }
+        if let Some(offset) = digits.find(|c: char| c != '_' && !c.is_digit(radix)) {
+            let invalid = digits_start + offset;
+            let message = format!("Invalid digit '{}' in {name} literal.", self.char_at(invalid));
+            self.number_error(&message, invalid, invalid + 1);
+            return Number(0.0);
+        }
+        if !self.check_underscores(digits_start, radix) {
+            return Number(0.0);
+        }
+
+        let value = digits.chars()
+            .filter_map(|c| c.to_digit(radix))
+            .fold(0.0, |value, digit| value * radix as f64 + digit as f64);
+        Number(value)
+    }
+
+    /// Consumes a run of decimal digits and underscores.
+    fn digits(&mut self) {
+        while is_digit(self.peek()) || self.peek() == '_' {
+            self.advance();
+        }
+    }
+
+    /// Checks that every underscore in the number scanned from `from` sits
+    /// between two digits. Reports the first one that doesn't.
+    fn check_underscores(&mut self, from: usize, radix: u32) -> bool {
+        let is_digit = |byte: u8| (byte as char).is_digit(radix);
+        let text = &self.source[from..self.current];
+        for (i, &byte) in text.iter().enumerate() {
+            if byte != b'_' {
+                continue;
+            }
+            let offset = from + i;
+            if i > 0 && text[i - 1] == b'_' {
+                self.number_error("Consecutive underscores in number literal.", offset, offset + 1);
+                return false;
+            }
+            let before = i > 0 && is_digit(text[i - 1]);
+            let after = text.get(i + 1).is_some_and(|&next| next == b'_' || is_digit(next));
+            if !before || !after {
+                self.number_error("Underscores in a number must separate digits.", offset, offset + 1);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Reports an error on part of the number being scanned. Numbers are
+    /// ASCII, so columns can be worked out from byte offsets.
+    fn number_error(&mut self, message: &str, from: usize, to: usize) {
+        let column = self.start_column + (from - self.start);
+        let span = Span::new(from, to, self.start_line, column);
+        self.diagnostics.push(Diagnostic::error(SCAN_ERROR, message, span));
+    }
+
     fn identifier(&mut self) -> Option<TokenType> {
         while is_alpha_numeric(self.peek()) {
             self.advance();
         }
-        let value: &str = std::str::from_utf8(&self.source[self.start+1..self.current-1]).expect("Invalid utf8 sequence");
+        let value: &str = &self.text[self.start..self.current];
         let token_type: TokenType = KEYWORDS.with(|k| match k.get(value) {
             Some(c) => c.clone(),
             None => Identifier
@@ -195,11 +482,11 @@ impl <'a> LoxScanner<'a> {
 }
 
 fn is_alpha(c: char) -> bool {
-    (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') ||  c == '_'
+    c.is_ascii_lowercase() || c.is_ascii_uppercase() || c == '_'
 }
 
 fn is_digit(c: char) -> bool {
-    c >= '0' && c <= '9'
+    c.is_ascii_digit()
 }
 
 fn is_alpha_numeric(c: char) -> bool {
@@ -209,14 +496,319 @@ fn is_alpha_numeric(c: char) -> bool {
 
 impl Scanner for LoxScanner<'_> {
     fn scan_tokens(&mut self) -> Vec<Token> {
-        while !self.is_at_end() {
+        // Source that isn't valid UTF-8 is reported and not scanned at all.
+        if self.check_utf8() {
+            while !self.is_at_end() {
 
-            self.start = self.current;
-            self.scan_token();
+                self.start = self.current;
+                self.start_line = self.line;
+                self.start_column = self.column;
+                self.scan_token();
+            }
         }
 
-        self.tokens.push(Token::new(EOF, "".to_string(), "".to_string(), self.line));
+        let span = Span::new(self.current, self.current, self.line, self.column);
+        self.tokens.push(Token::new(EOF, "".to_string(), None, span));
 
         self.tokens.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> (Vec<Token>, Vec<Diagnostic>) {
+        let mut scanner = LoxScanner::new(source.as_bytes());
+        let tokens = scanner.scan_tokens();
+        (tokens, scanner.diagnostics().to_vec())
+    }
+
+    fn token_types(source: &str) -> Vec<TokenType> {
+        let (tokens, diagnostics) = scan(source);
+        assert!(diagnostics.is_empty(), "unexpected diagnostics for {source:?}: {diagnostics:?}");
+        tokens.into_iter().map(|token| token.token_type).collect()
+    }
+
+    fn error_messages(source: &str) -> Vec<String> {
+        let (_, diagnostics) = scan(source);
+        diagnostics.into_iter().map(|diagnostic| diagnostic.message).collect()
+    }
+
+    #[test]
+    fn scans_every_token_type() {
+        let cases = [
+            ("(", LeftParen),
+            (")", RightParen),
+            ("{", LeftBrace),
+            ("}", RightBrace),
+            (",", Comma),
+            (".", Dot),
+            ("-", Minus),
+            ("+", Plus),
+            (";", SemiColon),
+            ("/", Slash),
+            ("*", Star),
+            ("!", Bang),
+            ("!=", BangEqual),
+            ("=", Equal),
+            ("==", EqualEqual),
+            (">", Greater),
+            (">=", GreaterEqual),
+            ("<", Less),
+            ("<=", LessEqual),
+            ("name", Identifier),
+            ("\"text\"", LoxString("text".to_string())),
+            ("42", Number(42.0)),
+            ("and", And),
+            ("class", Class),
+            ("else", Else),
+            ("false", False),
+            ("fun", Fun),
+            ("for", For),
+            ("if", If),
+            ("nil", Nil),
+            ("or", Or),
+            ("print", Print),
+            ("return", Return),
+            ("super", Super),
+            ("this", This),
+            ("true", True),
+            ("var", Var),
+            ("while", While),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(token_types(source), vec![expected, EOF], "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn scans_end_of_input() {
+        let cases = [
+            ("", vec![EOF]),
+            (" \t\r\n", vec![EOF]),
+            ("a", vec![Identifier, EOF]),
+            ("1", vec![Number(1.0), EOF]),
+            ("a\n", vec![Identifier, EOF]),
+            ("a b", vec![Identifier, Identifier, EOF]),
+            ("1+2", vec![Number(1.0), Plus, Number(2.0), EOF]),
+            ("!", vec![Bang, EOF]),
+            ("a=", vec![Identifier, Equal, EOF]),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(token_types(source), expected, "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn keeps_the_full_lexeme_of_identifiers() {
+        let cases = [
+            ("x", Identifier, "x"),
+            ("_", Identifier, "_"),
+            ("or", Or, "or"),
+            ("orchid", Identifier, "orchid"),
+            ("classy", Identifier, "classy"),
+            ("var_1", Identifier, "var_1"),
+        ];
+        for (source, token_type, lexeme) in cases {
+            let (tokens, _) = scan(source);
+            assert_eq!(tokens[0].token_type, token_type, "scanning {source:?}");
+            assert_eq!(tokens[0].lexeme, lexeme, "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn skips_comments() {
+        let cases = [
+            ("// nothing else", vec![EOF]),
+            ("//", vec![EOF]),
+            ("1 // one\n2", vec![Number(1.0), Number(2.0), EOF]),
+            ("// first\n// second\n", vec![EOF]),
+            ("1 / 2", vec![Number(1.0), Slash, Number(2.0), EOF]),
+            ("/* block */", vec![EOF]),
+            ("/**/1", vec![Number(1.0), EOF]),
+            ("1 /* a\nb */ 2", vec![Number(1.0), Number(2.0), EOF]),
+            ("/* outer /* inner */ still outer */ 1", vec![Number(1.0), EOF]),
+            ("/* // line comment inside */ 1", vec![Number(1.0), EOF]),
+            ("// /* not a block\n1", vec![Number(1.0), EOF]),
+            ("1 */ 2", vec![Number(1.0), Star, Slash, Number(2.0), EOF]),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(token_types(source), expected, "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn scans_numbers() {
+        let cases = [
+            ("0", vec![Number(0.0), EOF]),
+            ("1.5", vec![Number(1.5), EOF]),
+            ("1.", vec![Number(1.0), Dot, EOF]),
+            ("1.a", vec![Number(1.0), Dot, Identifier, EOF]),
+            (".5", vec![Dot, Number(5.0), EOF]),
+            ("1.2.3", vec![Number(1.2), Dot, Number(3.0), EOF]),
+            ("-7", vec![Minus, Number(7.0), EOF]),
+            ("0xFF", vec![Number(255.0), EOF]),
+            ("0Xff_ff", vec![Number(65535.0), EOF]),
+            ("0b1010", vec![Number(10.0), EOF]),
+            ("0B1111_0000", vec![Number(240.0), EOF]),
+            ("1_000_000", vec![Number(1_000_000.0), EOF]),
+            ("1_000.000_1", vec![Number(1000.0001), EOF]),
+            ("6.02e23", vec![Number(6.02e23), EOF]),
+            ("1E3", vec![Number(1000.0), EOF]),
+            ("2.5e-3", vec![Number(0.0025), EOF]),
+            ("1e+1_0", vec![Number(1e10), EOF]),
+            ("1else", vec![Number(1.0), Else, EOF]),
+            ("0.x", vec![Number(0.0), Dot, Identifier, EOF]),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(token_types(source), expected, "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn scans_strings() {
+        let cases = [
+            ("\"\"", vec![LoxString("".to_string()), EOF]),
+            ("\"a b\"", vec![LoxString("a b".to_string()), EOF]),
+            ("\"two\nlines\"", vec![LoxString("two\nlines".to_string()), EOF]),
+            ("\"é\"", vec![LoxString("é".to_string()), EOF]),
+            (r#""a\nb""#, vec![LoxString("a\nb".to_string()), EOF]),
+            (r#""\t\r\0""#, vec![LoxString("\t\r\0".to_string()), EOF]),
+            (r#""say \"hi\"""#, vec![LoxString("say \"hi\"".to_string()), EOF]),
+            (r#""back\\slash""#, vec![LoxString("back\\slash".to_string()), EOF]),
+            (r#""\\""#, vec![LoxString("\\".to_string()), EOF]),
+            (r#""\u{41}\u{e9}\u{1F600}""#, vec![LoxString("Aé😀".to_string()), EOF]),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(token_types(source), expected, "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn carries_literal_values() {
+        let cases = [
+            ("\"123\"", Some(LiteralValue::Str("123".to_string()))),
+            ("123", Some(LiteralValue::Number(123.0))),
+            ("nil", None),
+            ("name", None),
+        ];
+        for (source, expected) in cases {
+            let (tokens, _) = scan(source);
+            assert_eq!(tokens[0].literal, expected, "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        let cases = [
+            ("\"open", vec!["Unterminated string."]),
+            ("\"", vec!["Unterminated string."]),
+            ("\"a\nb", vec!["Unterminated string."]),
+            ("@", vec!["Unexpected character."]),
+            ("a # b $", vec!["Unexpected character.", "Unexpected character."]),
+            ("/* open", vec!["Unterminated block comment."]),
+            ("/* /* nested */", vec!["Unterminated block comment."]),
+            ("/*", vec!["Unterminated block comment."]),
+            (r#""\q""#, vec!["Unknown escape sequence '\\q'."]),
+            (r#""\u41""#, vec!["Expect '{' after '\\u'."]),
+            (r#""\u{}""#, vec!["Expect 1 to 6 hex digits in unicode escape."]),
+            (r#""\u{1234567}""#, vec!["Expect 1 to 6 hex digits in unicode escape."]),
+            (r#""\u{41""#, vec!["Expect '}' after unicode escape."]),
+            (r#""\u{D800}""#, vec!["Invalid unicode code point."]),
+            (r#""\u{110000}""#, vec!["Invalid unicode code point."]),
+            (r#""abc\"#, vec!["Unterminated string."]),
+            ("0x", vec!["Expect hex digits after '0x'."]),
+            ("0b;", vec!["Expect binary digits after '0b'."]),
+            ("0b102", vec!["Invalid digit '2' in binary literal."]),
+            ("0xFG", vec!["Invalid digit 'G' in hex literal."]),
+            ("1__0", vec!["Consecutive underscores in number literal."]),
+            ("1_", vec!["Underscores in a number must separate digits."]),
+            ("1_.5", vec!["Underscores in a number must separate digits."]),
+            ("0x_1", vec!["Underscores in a number must separate digits."]),
+            ("1e_5", vec!["Underscores in a number must separate digits."]),
+            ("1e+", vec!["Expect digits in exponent."]),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(error_messages(source), expected, "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn reports_invalid_utf8_once_per_line() {
+        let mut scanner = LoxScanner::new(b"\xff\xfe\nok\n\xff");
+        let tokens = scanner.scan_tokens();
+        let lines: Vec<usize> = scanner.diagnostics().iter().map(|diagnostic| diagnostic.span.line).collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(tokens.len(), 1);
+    }
+
+    #[test]
+    fn reports_unterminated_block_comment_where_it_opened() {
+        let (tokens, diagnostics) = scan("1\n  /* a\n\n b");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].span.line, diagnostics[0].span.column), (2, 3));
+        let eof = tokens.last().unwrap();
+        assert_eq!(eof.span.line, 4);
+    }
+
+    #[test]
+    fn reports_invalid_escapes_at_their_column() {
+        let (tokens, diagnostics) = scan("\"ok\"\n  \"a\\qb\"");
+        assert_eq!(diagnostics.len(), 1);
+        let span = diagnostics[0].span;
+        assert_eq!((span.line, span.column, span.end - span.start), (2, 5, 2));
+        assert_eq!(tokens[1].lexeme, "\"a\\qb\"");
+        assert_eq!(tokens[1].token_type, LoxString("ab".to_string()));
+    }
+
+    #[test]
+    fn keeps_escapes_in_the_lexeme() {
+        let (tokens, _) = scan(r#""tab\there""#);
+        assert_eq!(tokens[0].lexeme, r#""tab\there""#);
+        assert_eq!(tokens[0].literal, Some(LiteralValue::Str("tab\there".to_string())));
+    }
+
+    #[test]
+    fn reports_malformed_numbers_at_the_offending_char() {
+        let cases = [
+            ("x = 1__0;", (7, 8)),
+            ("x = 0x;", (5, 7)),
+            ("x = 0b12;", (8, 9)),
+            ("x = 1e-;", (6, 8)),
+        ];
+        for (source, (start, end)) in cases {
+            let (tokens, diagnostics) = scan(source);
+            assert_eq!(diagnostics.len(), 1, "scanning {source:?}");
+            let span = diagnostics[0].span;
+            assert_eq!((span.column, span.column + span.end - span.start), (start, end), "scanning {source:?}");
+            // A token is still produced so the parser can carry on.
+            assert!(matches!(tokens[2].token_type, Number(_)), "scanning {source:?}");
+        }
+    }
+
+    #[test]
+    fn prints_tokens_one_per_line() {
+        let (tokens, _) = scan("print \"a\nb\" + 0x10;");
+        let lines: Vec<String> = tokens.iter().map(Token::to_string).collect();
+        assert_eq!(lines, vec![
+            "1:1 PRINT 'print'",
+            "1:7 STRING '\"a\\nb\"' \"a\\nb\"",
+            "2:4 PLUS '+'",
+            "2:6 NUMBER '0x10' 16",
+            "2:10 SEMICOLON ';'",
+            "2:11 EOF ''",
+        ]);
+        assert_eq!(
+            tokens[1].to_json().to_string(),
+            r#"{"line":1,"column":7,"type":"STRING","lexeme":"\"a\nb\"","literal":"a\nb","start":6,"end":11}"#,
+        );
+    }
+
+    #[test]
+    fn records_token_positions() {
+        let (tokens, _) = scan("var a\n  = \"x\ny\";");
+        let positions: Vec<(usize, usize)> = tokens.iter().map(|token| (token.span.line, token.span.column)).collect();
+        assert_eq!(positions, vec![(1, 1), (1, 5), (2, 3), (2, 5), (3, 3), (3, 4)]);
+    }
+}
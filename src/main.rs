@@ -5,115 +5,412 @@ mod lox_scanner;
 mod ast_printer;
 mod expression;
 mod lox_parser;
+mod interpreter;
+mod value;
+mod statement;
+mod environment;
+mod lox_callable;
+mod lox_class;
+mod lox_instance;
+mod lox_function;
+mod natives;
+mod resolver;
+mod span;
+mod diagnostic;
+mod renderer;
+mod repl;
+mod line_editor;
+mod json;
+mod ast_json;
+mod ast_dot;
+#[cfg(test)]
+mod test_support;
 
-use std::{env, io};
-use std::cell::RefCell;
-use std::fs::{File};
-use std::io::{Error, ErrorKind, Read, Write};
+use std::{env, fs, io};
+use std::io::{IsTerminal, Read};
+use std::path::Path;
 use std::process::exit;
+use std::thread;
+use crate::ast_dot::AstDot;
+use crate::ast_json::AstJson;
 use crate::ast_printer::AstPrinter;
-use crate::expression::{Binary, Expr, Grouping, Literal, Unary, VisitedElement};
+use crate::diagnostic::{Diagnostic, RUNTIME_ERROR};
+use crate::interpreter::Interpreter;
+use crate::json::Json;
+use crate::lox_parser::LoxParser;
+use crate::renderer::Renderer;
+use crate::repl::Repl;
+use crate::resolver::Resolver;
 use crate::scanner::Scanner;
-use crate::token::Token;
-use crate::token_type::TokenType;
+use crate::statement::Stmt;
 
+// Exit codes, following sysexits.h.
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_NOINPUT: i32 = 66;
+const EX_SOFTWARE: i32 = 70;
+
+const USAGE: &str = "Usage: rlox [command] [args]
+
+Commands:
+  run <file>     Run a script
+  repl           Start an interactive prompt (the default)
+  tokens <file>  Print the tokens of a script, one per line as
+                 `line:col TYPE 'lexeme' literal`
+  ast <file>     Print the syntax tree of a script
+  check <file>   Report errors in a script without running it
+
+Options:
+  --json             With `tokens`, print the tokens as JSON lines instead
+  --dump-tokens      Same as the `tokens` command
+  --format <format>  With `ast`, print the tree as `lisp` (the default),
+                     `json`, or `dot` for a Graphviz graph
+  --ast              With `run`, read a syntax tree saved by
+                     `ast --format json` instead of a script
+
+Pass `-` as the file to read the script from standard input.";
+
+const COMMANDS: [&str; 6] = ["run", "repl", "tokens", "ast", "check", "help"];
+
+enum Command {
+    Run(String),
+    /// Run a program from its syntax tree in JSON.
+    RunAst(String),
+    Repl,
+    Tokens(String, TokenFormat),
+    Ast(String, AstFormat),
+    Check(String),
+    Help,
+}
+
+#[derive(Clone, Copy)]
+pub enum TokenFormat {
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+#[derive(Clone, Copy)]
+pub enum AstFormat {
+    Lisp,
+    Json,
+    /// A Graphviz graph.
+    Dot,
+}
 
 fn main() {
-    let expr = Expr::Binary(Binary::new(
-        Box::new(Expr::Unary(Unary::new(
-            Token::new(TokenType::Minus, "-".to_string(), "".to_string(), 1),
-            Box::new(Expr::Literal(Literal::new(Some("123".to_string())))),
-        ))),
-        Token::new(TokenType::Star, "*".to_string(), "".to_string(), 1),
-        Box::new(Expr::Grouping(Grouping::new(
-            Box::new(Expr::Literal(Literal::new(Some("45.67".to_string()))))
-        )))
-    ));
-    let printer: AstPrinter = AstPrinter { };
-    let pretty = expr.accept(printer);
-
-    println!("{pretty}");
-}
-
-// fn main() {
-//     let args: Vec<String> = env::args().collect();
-//
-//     if args.len() > 2 {
-//         println!("Usage: rlox [script]");
-//     } else if let Some(filename) = args.get(1) {
-//         run_file(filename).expect("File should exist");
-//         if has_global_error() { exit(65) }
-//     } else {
-//         run_prompt()
-//     }
-// }
-
-fn run_file(filename: &String) -> Result<(), Error> {
-    // let file = read_to_string(filename.clone());
-    let mut file = File::open(filename.clone()).unwrap();
-    let mut buf: Vec<u8> = Vec::new();
-    let res = file.read_to_end(&mut buf);
-
-    if res.is_err() {
-        return Err(Error::new(ErrorKind::NotFound, format!("file {} does not exist!", filename)));
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("rlox: {message}\n\n{USAGE}");
+            exit(EX_USAGE)
+        }
+    };
+
+    // Parsing and running Lox recurse on the Rust stack, so give them enough
+    // of it to reach their own nesting limits.
+    let status = thread::Builder::new()
+        .stack_size(interpreter::STACK_SIZE)
+        .spawn(move || run_command(command))
+        .and_then(|handle| handle.join().map_err(|_| io::Error::other("interpreter thread panicked")))
+        .unwrap_or_else(|error| {
+            eprintln!("rlox: {error}");
+            EX_SOFTWARE
+        });
+    exit(status)
+}
+
+fn run_command(command: Command) -> i32 {
+    match command {
+        Command::Run(path) => with_source(&path, run_file),
+        Command::RunAst(path) => with_source(&path, run_ast_file),
+        Command::Repl => {
+            Repl::new().run();
+            0
+        }
+        Command::Tokens(path, format) => with_source(&path, |name, source| print_tokens(name, source, format)),
+        Command::Ast(path, format) => with_source(&path, |name, source| print_ast(name, source, format)),
+        Command::Check(path) => with_source(&path, check_file),
+        Command::Help => {
+            println!("{USAGE}");
+            0
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut positional: Vec<&str> = vec![];
+    let mut json = false;
+    let mut from_ast = false;
+    let mut format: Option<&str> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--ast" => from_ast = true,
+            "--format" => format = Some(args.next().ok_or("`--format` expects a value")?),
+            "--dump-tokens" => positional.push("tokens"),
+            "-h" | "--help" => return Ok(Command::Help),
+            option if option.starts_with("--") => return Err(format!("unknown option `{option}`")),
+            arg => positional.push(arg),
+        }
+    }
+
+    let (command, rest) = positional.split_first().unwrap_or((&"repl", &[]));
+    if !COMMANDS.contains(command) {
+        // Scripts used to be run with `rlox script.lox`.
+        if *command == "-" || command.ends_with(".lox") || Path::new(command).is_file() {
+            return Err(format!("unknown command `{command}`, did you mean `rlox run {command}`?"));
+        }
+        return Err(format!("unknown command `{command}`"));
+    }
+
+    let path = match rest {
+        [] if matches!(*command, "repl" | "help") => String::new(),
+        _ if matches!(*command, "repl" | "help") => return Err(format!("`{command}` takes no arguments")),
+        [path] => path.to_string(),
+        [] => return Err(format!("`{command}` expects a file")),
+        _ => return Err(format!("`{command}` expects a single file")),
+    };
+
+    // Options only apply to one command each.
+    let allowed = match *command {
+        "run" => !json && format.is_none(),
+        "tokens" => !from_ast && format.is_none(),
+        "ast" => !json && !from_ast,
+        _ => !json && !from_ast && format.is_none(),
+    };
+    if !allowed {
+        return Err(format!("unsupported option for `{command}`"));
+    }
+
+    match *command {
+        "run" if from_ast => Ok(Command::RunAst(path)),
+        "run" => Ok(Command::Run(path)),
+        "repl" => Ok(Command::Repl),
+        "tokens" if json => Ok(Command::Tokens(path, TokenFormat::Json)),
+        "tokens" => Ok(Command::Tokens(path, TokenFormat::Text)),
+        "ast" => match format.unwrap_or("lisp") {
+            "lisp" => Ok(Command::Ast(path, AstFormat::Lisp)),
+            "json" => Ok(Command::Ast(path, AstFormat::Json)),
+            "dot" => Ok(Command::Ast(path, AstFormat::Dot)),
+            other => Err(format!("unknown AST format `{other}`")),
+        },
+        "check" => Ok(Command::Check(path)),
+        "help" => Ok(Command::Help),
+        _ => unreachable!("commands are checked against COMMANDS"),
+    }
+}
+
+/// Reads the script at `path`, or standard input for `-`, and hands it to
+/// `action` under the name to show in diagnostics. Returns the exit code.
+fn with_source(path: &str, action: impl FnOnce(&str, &[u8]) -> i32) -> i32 {
+    let (name, source) = if path == "-" {
+        let mut source = Vec::new();
+        ("<stdin>", io::stdin().read_to_end(&mut source).map(|_| source))
     } else {
-        run(buf.as_slice());
-        Ok(())
-    }
-}
-
-fn run_prompt() {
-    println!("Starting prompt");
-    let mut input = String::new();
-    loop {
-        print!("λ ");
-        let _ = io::stdout().flush();
-        match io::stdin().read_line(&mut input) {
-            Ok(0) => {
-                // CTRL-D
-                println!("Stopping prompt...");
-                break;
-            }
-            Ok(n) => {
-                println!("{} bytes read", n);
-                println!("{}", input);
-            }
-            Err(error) => println!("error: {error}"),
+        (path, fs::read(path))
+    };
+
+    match source {
+        Ok(source) => action(name, &source),
+        Err(error) => {
+            eprintln!("rlox: can't read {name}: {error}");
+            EX_NOINPUT
+        }
+    }
+}
+
+fn run_file(name: &str, source: &[u8]) -> i32 {
+    let mut interpreter = Interpreter::new();
+    let diagnostics = run(source, &mut interpreter);
+    render(&diagnostics, name, source);
+    exit_code(&diagnostics)
+}
+
+/// Runs a program from the JSON saved by `rlox ast --format json`.
+fn run_ast_file(name: &str, source: &[u8]) -> i32 {
+    let program = std::str::from_utf8(source)
+        .map_err(|_| "not valid UTF-8".to_string())
+        .and_then(|text| Json::parse(text).map_err(|error| error.message))
+        .and_then(|json| ast_json::program_from_json(&json).map_err(|error| error.message));
+    let statements = match program {
+        Ok(statements) => statements,
+        Err(message) => {
+            eprintln!("rlox: invalid syntax tree in {name}: {message}");
+            return EX_DATAERR;
+        }
+    };
+
+    let mut interpreter = Interpreter::new();
+    let mut diagnostics = vec![];
+    execute(&statements, &mut interpreter, &mut diagnostics);
+    // There is no source to quote, only the positions in the tree.
+    render(&diagnostics, name, &[]);
+    exit_code(&diagnostics)
+}
+
+fn print_tokens(name: &str, source: &[u8], format: TokenFormat) -> i32 {
+    let mut scanner = lox_scanner::LoxScanner::new(source);
+    for token in scanner.scan_tokens() {
+        match format {
+            TokenFormat::Text => println!("{token}"),
+            TokenFormat::Json => println!("{}", token.to_json()),
         }
-        run(input.as_bytes());
-        set_global_error(false);
-        input.clear();
     }
+    let diagnostics = scanner.diagnostics();
+    render(diagnostics, name, source);
+    exit_code(diagnostics)
 }
 
-fn run(source: &[u8]) {
+fn print_ast(name: &str, source: &[u8], format: AstFormat) -> i32 {
+    let mut diagnostics = vec![];
+    if let Some(statements) = parse(source, &mut diagnostics) {
+        match format {
+            AstFormat::Lisp => print!("{}", AstPrinter::new().print(&statements)),
+            AstFormat::Json => println!("{}", AstJson.program(&statements)),
+            AstFormat::Dot => print!("{}", AstDot::new().graph(&statements)),
+        }
+    }
+    render(&diagnostics, name, source);
+    exit_code(&diagnostics)
+}
+
+fn check_file(name: &str, source: &[u8]) -> i32 {
+    let mut diagnostics = vec![];
+    if let Some(statements) = parse(source, &mut diagnostics) {
+        resolve(&statements, &mut Interpreter::new(), &mut diagnostics);
+    }
+    render(&diagnostics, name, source);
+    exit_code(&diagnostics)
+}
+
+/// Scans and parses a source. Returns the statements only if there was
+/// no error.
+fn parse(source: &[u8], diagnostics: &mut Vec<Diagnostic>) -> Option<Vec<Stmt>> {
     let mut scanner = lox_scanner::LoxScanner::new(source);
     let tokens = scanner.scan_tokens();
-    tokens.iter().for_each(|t| println!("Token:{:?}", t));
+    diagnostics.extend_from_slice(scanner.diagnostics());
+
+    let mut parser = LoxParser::new(tokens);
+    let statements = parser.parse();
+    diagnostics.extend_from_slice(parser.diagnostics());
+
+    let statements = statements.ok()?;
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return None;
+    }
+    Some(statements)
+}
+
+/// Runs the resolver over parsed statements, recording the scope distances
+/// in `interpreter`. Returns false if there was an error.
+fn resolve(statements: &[Stmt], interpreter: &mut Interpreter, diagnostics: &mut Vec<Diagnostic>) -> bool {
+    let mut resolver = Resolver::new(interpreter);
+    resolver.resolve(statements);
+    diagnostics.extend_from_slice(resolver.diagnostics());
+    !resolver.diagnostics().iter().any(Diagnostic::is_error)
 }
 
-fn error(line: usize, message: &str) {
-    report(line, "", message);
+/// Runs one source through every phase, stopping after the first phase that
+/// reports an error. Returns everything that was reported.
+fn run(source: &[u8], interpreter: &mut Interpreter) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    if let Some(statements) = parse(source, &mut diagnostics) {
+        execute(&statements, interpreter, &mut diagnostics);
+    }
+    diagnostics
 }
 
-fn report(line: usize, location: &str, message: &str) {
-    println!("[line {line}] Error {location}: {message}");
-    set_global_error(true);
+/// Resolves and interprets parsed statements, unless resolving fails.
+fn execute(statements: &[Stmt], interpreter: &mut Interpreter, diagnostics: &mut Vec<Diagnostic>) {
+    if !resolve(statements, interpreter, diagnostics) {
+        return;
+    }
+
+    if let Err(error) = interpreter.interpret(statements) {
+        diagnostics.push(error.into());
+    }
 }
 
-// Global error flag
-// set to true when an error is encountered
-thread_local!(static HAS_ERROR: RefCell<bool> = RefCell::new(false));
+/// Exit status for a script that produced these diagnostics: 70 if it
+/// failed at runtime, 65 if it was rejected before running, 0 otherwise.
+fn exit_code(diagnostics: &[Diagnostic]) -> i32 {
+    let mut errors = diagnostics.iter().filter(|diagnostic| diagnostic.is_error()).peekable();
+    if errors.peek().is_none() {
+        0
+    } else if errors.any(|diagnostic| diagnostic.code == RUNTIME_ERROR) {
+        EX_SOFTWARE
+    } else {
+        EX_DATAERR
+    }
+}
 
-fn has_global_error() -> bool {
-    HAS_ERROR.with(|has_error| {
-        return *has_error.borrow();
-    })
+/// Prints diagnostics to stderr, so they never mix with the tokens or trees
+/// a command prints on stdout.
+fn render(diagnostics: &[Diagnostic], file_name: &str, source: &[u8]) {
+    let renderer = Renderer::new(file_name, source, io::stderr().is_terminal());
+    for diagnostic in diagnostics {
+        eprintln!("{}", renderer.render(diagnostic));
+    }
 }
 
-fn set_global_error(new_value: bool) {
-    HAS_ERROR.with(|has_error| {
-        let mut has_error = has_error.borrow_mut();
-        *has_error = new_value;
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Severity;
+    use crate::span::Span;
+
+    fn usage_error(args: &str) -> Option<String> {
+        let args: Vec<String> = args.split_whitespace().map(str::to_string).collect();
+        parse_args(&args).err()
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        let cases = [
+            ("foo", "unknown command `foo`"),
+            ("foo bar", "unknown command `foo`"),
+            ("-", "unknown command `-`, did you mean `rlox run -`?"),
+            ("script.lox", "unknown command `script.lox`, did you mean `rlox run script.lox`?"),
+            ("run", "`run` expects a file"),
+            ("check a.lox b.lox", "`check` expects a single file"),
+            ("repl a.lox", "`repl` takes no arguments"),
+            ("tokens --format json a.lox", "unsupported option for `tokens`"),
+            ("ast --format svg a.lox", "unknown AST format `svg`"),
+            ("run --verbose a.lox", "unknown option `--verbose`"),
+        ];
+        for (args, message) in cases {
+            assert_eq!(usage_error(args).as_deref(), Some(message), "parsing {args:?}");
+        }
+        assert_eq!(usage_error(""), None);
+        assert_eq!(usage_error("run -"), None);
+        assert_eq!(usage_error("--dump-tokens --json a.lox"), None);
+    }
+
+    #[test]
+    fn exits_by_the_phase_that_failed() {
+        let cases = [
+            ("var a = 1;", 0),
+            ("var a = \"unterminated;", EX_DATAERR),
+            ("var = 1;", EX_DATAERR),
+            ("return 1;", EX_DATAERR),
+            ("var a = -\"a\";", EX_SOFTWARE),
+        ];
+        for (source, code) in cases {
+            let diagnostics = run(source.as_bytes(), &mut Interpreter::new());
+            assert_eq!(exit_code(&diagnostics), code, "running {source:?}");
+        }
+    }
+
+    #[test]
+    fn exits_successfully_with_only_warnings() {
+        let mut warning = Diagnostic::error(RUNTIME_ERROR, "Not really a problem.", Span::new(0, 0, 1, 1));
+        warning.severity = Severity::Warning;
+        assert_eq!(exit_code(&[]), 0);
+        assert_eq!(exit_code(&[warning]), 0);
+    }
 }
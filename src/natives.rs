@@ -0,0 +1,26 @@
+use std::fmt;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::interpreter::{Interpreter, RuntimeError};
+use crate::lox_callable::LoxCallable;
+use crate::value::Value;
+
+/// `clock()`: seconds elapsed since the Unix epoch.
+pub struct Clock;
+
+impl LoxCallable for Clock {
+    fn arity(&self) -> usize {
+        0
+    }
+
+    fn call(self: Rc<Self>, _interpreter: &mut Interpreter, _arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+        let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Ok(Value::Number(elapsed.as_secs_f64()))
+    }
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native fn>")
+    }
+}
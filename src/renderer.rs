@@ -0,0 +1,240 @@
+use std::fmt::Write;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::span::Span;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Tabs are expanded so that underlines line up with the source above them.
+const TAB_WIDTH: usize = 4;
+
+/// Formats diagnostics the way rustc does: a header with the error code,
+/// the `file:line:col` location, then every source line involved with the
+/// primary span underlined `^~~~` and secondary labels underlined `---`.
+pub struct Renderer<'a> {
+    file_name: &'a str,
+    source: &'a [u8],
+    colour: bool,
+}
+
+/// One underline to draw below a source line.
+struct Annotation<'a> {
+    span: Span,
+    primary: bool,
+    message: &'a str,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(file_name: &'a str, source: &'a [u8], colour: bool) -> Self {
+        Renderer {
+            file_name,
+            source,
+            colour,
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut annotations = vec![Annotation { span: diagnostic.span, primary: true, message: "" }];
+        for label in diagnostic.labels.iter() {
+            annotations.push(Annotation { span: label.span, primary: false, message: &label.message });
+        }
+        annotations.sort_by_key(|annotation| (annotation.span.line, annotation.span.start));
+
+        let last_line = annotations.iter().map(|annotation| annotation.span.line).max().unwrap_or(1);
+        let gutter = " ".repeat(last_line.to_string().len());
+        let pipe = self.paint(BLUE, "|");
+
+        let severity = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        let mut out = String::new();
+        let header = format!("{}[{}]", diagnostic.severity, diagnostic.code);
+        let _ = writeln!(out, "{}{}", self.paint(severity, &header), self.paint(BOLD, &format!(": {}", diagnostic.message)));
+        let _ = writeln!(out, "{gutter}{} {}:{}:{}", self.paint(BLUE, "-->"), self.file_name, diagnostic.span.line, diagnostic.span.column);
+        let _ = writeln!(out, "{gutter} {pipe}");
+
+        // Without the source, as when running a saved syntax tree, only the
+        // location can be shown.
+        if self.source.is_empty() {
+            annotations.clear();
+        }
+
+        let mut previous_line: Option<usize> = None;
+        for annotation in annotations.iter() {
+            let line = annotation.span.line;
+            if previous_line != Some(line) {
+                if previous_line.is_some_and(|previous| line > previous + 1) {
+                    let _ = writeln!(out, "{}", self.paint(BLUE, "..."));
+                }
+                let number = format!("{line:>width$}", width = gutter.len());
+                let _ = writeln!(out, "{} {pipe} {}", self.paint(BLUE, &number), expand_tabs(&self.line_text(line)));
+                previous_line = Some(line);
+            }
+            let _ = writeln!(out, "{gutter} {pipe} {}", self.underline(annotation));
+        }
+
+        for note in diagnostic.notes.iter() {
+            let _ = writeln!(out, "{gutter} {} {}: {note}", self.paint(BLUE, "="), self.paint(BOLD, "note"));
+        }
+        if let Some(help) = &diagnostic.help {
+            let _ = writeln!(out, "{gutter} {} {}: {help}", self.paint(BLUE, "="), self.paint(BOLD, "help"));
+        }
+        out
+    }
+
+    fn underline(&self, annotation: &Annotation) -> String {
+        let line = self.line_text(annotation.span.line);
+        let start = annotation.span.column.saturating_sub(1);
+        let prefix: String = line.chars().take(start).collect();
+        let padding = " ".repeat(expand_tabs(&prefix).chars().count());
+
+        // Only the part of the span on its first line is underlined. Empty
+        // spans, such as the end of the input, still get a single mark.
+        let length = self.source.get(annotation.span.start..annotation.span.end)
+            .map(|text| expand_tabs(String::from_utf8_lossy(text).lines().next().unwrap_or("")).chars().count())
+            .unwrap_or(0)
+            .max(1);
+
+        if annotation.primary {
+            let marks = format!("^{}", "~".repeat(length - 1));
+            format!("{padding}{}", self.paint(RED, &marks))
+        } else {
+            let marks = format!("{} {}", "-".repeat(length), annotation.message);
+            format!("{padding}{}", self.paint(BLUE, &marks))
+        }
+    }
+
+    /// The text of a line of the source, counted from 1.
+    fn line_text(&self, line: usize) -> String {
+        let text = self.source.split(|&byte| byte == b'\n').nth(line - 1).unwrap_or(&[]);
+        String::from_utf8_lossy(text).trim_end_matches('\r').to_string()
+    }
+
+    fn paint(&self, colour: &str, text: &str) -> String {
+        if self.colour {
+            format!("{colour}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::SYNTAX_ERROR;
+
+    fn render(source: &str, diagnostic: &Diagnostic) -> String {
+        Renderer::new("test.lox", source.as_bytes(), false).render(diagnostic)
+    }
+
+    #[test]
+    fn underlines_the_span() {
+        let source = "print 1 + ;";
+        let diagnostic = Diagnostic::error(SYNTAX_ERROR, "Expect expression.", Span::new(8, 9, 1, 9))
+            .with_note("a note")
+            .with_help("some help");
+        assert_eq!(render(source, &diagnostic), "\
+error[E0002]: Expect expression.
+ --> test.lox:1:9
+  |
+1 | print 1 + ;
+  |         ^
+  = note: a note
+  = help: some help
+");
+    }
+
+    #[test]
+    fn expands_tabs() {
+        let source = "\tprint\t\"a\" + 1;";
+        let diagnostic = Diagnostic::error(SYNTAX_ERROR, "Bad.", Span::new(7, 14, 1, 8));
+        assert_eq!(render(source, &diagnostic), "\
+error[E0002]: Bad.
+ --> test.lox:1:8
+  |
+1 |     print    \"a\" + 1;
+  |              ^~~~~~~
+");
+    }
+
+    #[test]
+    fn expands_tabs_inside_the_span() {
+        let source = "print \"\t\tx\" + 1;";
+        let diagnostic = Diagnostic::error(SYNTAX_ERROR, "Bad.", Span::new(6, 15, 1, 7));
+        assert_eq!(render(source, &diagnostic), "\
+error[E0002]: Bad.
+ --> test.lox:1:7
+  |
+1 | print \"        x\" + 1;
+  |       ^~~~~~~~~~~~~~~
+");
+    }
+
+    #[test]
+    fn shows_labels_on_earlier_lines() {
+        let source = "{\n  var a = 1;\n  var a = 2;\n}";
+        let diagnostic = Diagnostic::error(SYNTAX_ERROR, "Redeclared.", Span::new(21, 22, 3, 7))
+            .with_label(Span::new(8, 9, 2, 7), "declared here");
+        assert_eq!(render(source, &diagnostic), "\
+error[E0002]: Redeclared.
+ --> test.lox:3:7
+  |
+2 |   var a = 1;
+  |       - declared here
+3 |   var a = 2;
+  |       ^
+");
+    }
+
+    #[test]
+    fn elides_lines_between_annotations() {
+        let source = "var a = 1;\n\n\n\n\n\n\n\n\nvar a = 2;";
+        let diagnostic = Diagnostic::error(SYNTAX_ERROR, "Redeclared.", Span::new(23, 24, 10, 5))
+            .with_label(Span::new(4, 5, 1, 5), "declared here");
+        assert_eq!(render(source, &diagnostic), "\
+error[E0002]: Redeclared.
+  --> test.lox:10:5
+   |
+ 1 | var a = 1;
+   |     - declared here
+...
+10 | var a = 2;
+   |     ^
+");
+    }
+
+    #[test]
+    fn marks_empty_spans_at_the_end_of_input() {
+        let source = "print 1";
+        let diagnostic = Diagnostic::error(SYNTAX_ERROR, "Expect ';' after value.", Span::new(7, 7, 1, 8));
+        assert_eq!(render(source, &diagnostic), "\
+error[E0002]: Expect ';' after value.
+ --> test.lox:1:8
+  |
+1 | print 1
+  |        ^
+");
+    }
+
+    #[test]
+    fn shows_only_the_location_without_source() {
+        let diagnostic = Diagnostic::error(SYNTAX_ERROR, "No source.", Span::new(3, 5, 2, 4))
+            .with_help("some help");
+        assert_eq!(render("", &diagnostic), "\
+error[E0002]: No source.
+ --> test.lox:2:4
+  |
+  = help: some help
+");
+    }
+}
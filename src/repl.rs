@@ -0,0 +1,329 @@
+use std::{env, fs, io};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use crate::diagnostic::Diagnostic;
+use crate::expression::Expr;
+use crate::interpreter::Interpreter;
+use crate::line_editor;
+use crate::lox_parser::LoxParser;
+use crate::lox_scanner::LoxScanner;
+use crate::resolver::Resolver;
+use crate::scanner::Scanner;
+use crate::token_type::TokenType::{LeftBrace, LeftParen, RightBrace, RightParen};
+use crate::{print_ast, print_tokens, render, run, AstFormat, TokenFormat};
+
+const PROMPT: &str = "λ ";
+const CONTINUATION_PROMPT: &str = "… ";
+const SOURCE_NAME: &str = "<prompt>";
+const HISTORY_FILE: &str = ".rlox_history";
+/// Number of history entries kept, counting from the most recent.
+const HISTORY_SIZE: usize = 1000;
+
+const HELP: &str = "Enter Lox code to run it. An expression typed without a trailing ';'
+has its value printed. Input with unclosed braces or parentheses continues
+on the next line; enter an empty line to submit it anyway.
+
+The up and down arrows recall earlier lines, which are kept in
+~/.rlox_history and printed by :history. CTRL-C abandons the input and
+CTRL-D leaves the prompt.
+
+Commands:
+  :tokens <code>  Print the tokens of some code
+  :ast <code>     Print the syntax tree of some code
+  :env            List the global variables of the session
+  :load <file>    Run a script in the session
+  :reset          Start over with a fresh session
+  :history        Print the inputs of this and earlier sessions
+  :help           Print this message
+  :quit           Leave the prompt";
+
+/// The interactive prompt. Definitions persist across inputs until the
+/// session is reset.
+pub struct Repl {
+    interpreter: Interpreter,
+    history: History,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Repl {
+            interpreter: Interpreter::new(),
+            history: History::load(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        println!("rlox interactive prompt, :help for help.");
+        while let Some(input) = self.read_input() {
+            if input.trim().is_empty() {
+                continue;
+            }
+            if let Some(command) = input.trim().strip_prefix(':') {
+                // Only commands worth running again are recorded.
+                if !matches!(command, "quit" | "q" | "history") {
+                    self.history.add(&input);
+                }
+                if !self.meta_command(command) {
+                    break;
+                }
+            } else {
+                self.history.add(&input);
+                self.eval(&input);
+            }
+        }
+    }
+
+    /// Reads one input, which spans several lines while braces or parens
+    /// are left open. Returns `None` at the end of the input.
+    fn read_input(&mut self) -> Option<String> {
+        let mut input = String::new();
+        loop {
+            let prompt = if input.is_empty() { PROMPT } else { CONTINUATION_PROMPT };
+            let line = match line_editor::read_line(prompt, &self.history.entries) {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    // CTRL-D
+                    println!();
+                    return if input.is_empty() { None } else { Some(input) };
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {
+                    // CTRL-C drops the whole input, not just its last line.
+                    input.clear();
+                    continue;
+                }
+                Err(error) => {
+                    eprintln!("rlox: can't read input: {error}");
+                    return None;
+                }
+            };
+
+            let continuing = !input.is_empty();
+            input.push_str(&line);
+            input.push('\n');
+            if continuing && line.trim().is_empty() {
+                return Some(input);
+            }
+            if input.trim_start().starts_with(':') || !is_incomplete(&input) {
+                return Some(input);
+            }
+        }
+    }
+
+    /// Runs a meta-command, given without its leading `:`. Returns false if
+    /// the prompt should stop.
+    fn meta_command(&mut self, command: &str) -> bool {
+        let (name, argument) = split_command(command);
+
+        match name {
+            "tokens" => {
+                print_tokens(SOURCE_NAME, argument.as_bytes(), TokenFormat::Text);
+            }
+            "ast" => {
+                print_ast(SOURCE_NAME, argument.as_bytes(), AstFormat::Lisp);
+            }
+            "env" => {
+                for (name, value) in self.interpreter.globals.borrow().bindings() {
+                    println!("{name} = {value}");
+                }
+            }
+            "load" if argument.is_empty() => eprintln!(":load expects a file"),
+            "load" => match fs::read(argument) {
+                Ok(source) => {
+                    let diagnostics = run(&source, &mut self.interpreter);
+                    render(&diagnostics, argument, &source);
+                }
+                Err(error) => eprintln!("can't read {argument}: {error}"),
+            },
+            "reset" => {
+                self.interpreter = Interpreter::new();
+                println!("Session reset.");
+            }
+            "history" => {
+                for entry in self.history.entries.iter() {
+                    println!("{entry}");
+                }
+            }
+            "help" => println!("{HELP}"),
+            "quit" | "q" => return false,
+            _ => eprintln!("Unknown command ':{name}', :help lists the commands."),
+        }
+        true
+    }
+
+    fn eval(&mut self, input: &str) {
+        let diagnostics = match parse_bare_expression(input) {
+            Some(expression) => self.eval_expression(&expression),
+            None => run(input.as_bytes(), &mut self.interpreter),
+        };
+        render(&diagnostics, SOURCE_NAME, input.as_bytes());
+    }
+
+    /// Evaluates a bare expression and prints its value.
+    fn eval_expression(&mut self, expression: &Expr) -> Vec<Diagnostic> {
+        let mut resolver = Resolver::new(&mut self.interpreter);
+        resolver.resolve_expression(expression);
+        let diagnostics = resolver.diagnostics().to_vec();
+        if diagnostics.iter().any(Diagnostic::is_error) {
+            return diagnostics;
+        }
+
+        match self.interpreter.interpret_expression(expression) {
+            Ok(value) => {
+                println!("{value}");
+                diagnostics
+            }
+            Err(error) => vec![error.into()],
+        }
+    }
+}
+
+/// Splits a meta-command into its name and the rest of the line, which is
+/// empty for commands given without an argument.
+fn split_command(command: &str) -> (&str, &str) {
+    match command.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (command, ""),
+    }
+}
+
+/// Parses input made of a single expression without a trailing `;`.
+fn parse_bare_expression(input: &str) -> Option<Expr> {
+    let mut scanner = LoxScanner::new(input.as_bytes());
+    let tokens = scanner.scan_tokens();
+    if !scanner.diagnostics().is_empty() {
+        return None;
+    }
+    LoxParser::new(tokens).parse_expression()
+}
+
+/// Whether the input has more opening braces or parens than closing ones,
+/// meaning the user hasn't finished typing it.
+fn is_incomplete(input: &str) -> bool {
+    let tokens = LoxScanner::new(input.as_bytes()).scan_tokens();
+    let depth: i32 = tokens.iter()
+        .map(|token| match token.token_type {
+            LeftBrace | LeftParen => 1,
+            RightBrace | RightParen => -1,
+            _ => 0,
+        })
+        .sum();
+    depth > 0
+}
+
+/// Inputs typed at the prompt, kept in a file in the home directory so that
+/// they survive across sessions. Only the last `HISTORY_SIZE` entries are
+/// kept.
+struct History {
+    path: Option<PathBuf>,
+    entries: Vec<String>,
+}
+
+impl History {
+    fn load() -> Self {
+        let path = env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .map(|home| PathBuf::from(home).join(HISTORY_FILE));
+        let entries = path.as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|history| history.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        let mut history = History { path, entries };
+        if history.trim() {
+            history.save();
+        }
+        history
+    }
+
+    /// Records an input, one line of it per entry. Failing to save the
+    /// history is not worth interrupting the session for.
+    fn add(&mut self, input: &str) {
+        let lines: Vec<&str> = input.lines().filter(|line| !line.trim().is_empty()).collect();
+        self.entries.extend(lines.iter().map(|line| line.to_string()));
+        if self.trim() {
+            self.save();
+            return;
+        }
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            for line in lines {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+    }
+
+    /// Drops the oldest entries past `HISTORY_SIZE`. Returns whether there
+    /// were any.
+    fn trim(&mut self) -> bool {
+        let excess = self.entries.len().saturating_sub(HISTORY_SIZE);
+        self.entries.drain(..excess);
+        excess > 0
+    }
+
+    /// Rewrites the whole file with the entries kept.
+    fn save(&self) {
+        if let Some(path) = &self.path {
+            let _ = fs::write(path, self.entries.iter().map(|entry| format!("{entry}\n")).collect::<String>());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast_printer::AstPrinter;
+    use crate::expression::VisitedElement;
+
+    #[test]
+    fn waits_for_unclosed_brackets() {
+        let cases = [
+            ("print 1;", false),
+            ("fun f() {", true),
+            ("fun f() {\n  print (1 +", true),
+            ("fun f() {\n  print (1 + 2);\n}", false),
+            ("print \"{\";", false),
+            ("// {", false),
+            ("}", false),
+        ];
+        for (input, incomplete) in cases {
+            assert_eq!(is_incomplete(input), incomplete, "checking {input:?}");
+        }
+    }
+
+    #[test]
+    fn recognises_bare_expressions() {
+        let printed = |input: &str| parse_bare_expression(input).map(|expression| expression.accept(&mut AstPrinter::new()));
+        assert_eq!(printed("1 + 2").as_deref(), Some("(+ 1 2)"));
+        assert_eq!(printed("  a = b\n").as_deref(), Some("(= a b)"));
+        assert_eq!(printed("1 + 2;"), None);
+        assert_eq!(printed("print 1"), None);
+        assert_eq!(printed("var a = 1"), None);
+        assert_eq!(printed("1 +"), None);
+        assert_eq!(printed("\"unterminated"), None);
+    }
+
+    #[test]
+    fn splits_commands_from_their_argument() {
+        assert_eq!(split_command("env"), ("env", ""));
+        assert_eq!(split_command("load script.lox"), ("load", "script.lox"));
+        assert_eq!(split_command("tokens   1 + 2  "), ("tokens", "1 + 2"));
+        assert_eq!(split_command("ast\tprint 1;"), ("ast", "print 1;"));
+    }
+
+    #[test]
+    fn keeps_only_the_latest_history() {
+        let mut history = History { path: None, entries: vec![] };
+        history.add("print 1;\n\n  \nprint 2;\n");
+        assert_eq!(history.entries, vec!["print 1;", "print 2;"]);
+        assert!(!history.trim());
+
+        history.entries = (0..HISTORY_SIZE + 2).map(|i| i.to_string()).collect();
+        assert!(history.trim());
+        assert_eq!(history.entries.len(), HISTORY_SIZE);
+        assert_eq!(history.entries.first().map(String::as_str), Some("2"));
+        assert!(!history.trim());
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use crate::diagnostic::{Diagnostic, RESOLUTION_ERROR};
+use crate::expression;
+use crate::expression::{Assign, Binary, Call, Expr, Get, Grouping, Literal, Logical, Set, Super, This, Unary, Variable, VisitedElement};
+use crate::interpreter::Interpreter;
+use crate::statement;
+use crate::statement::{Block, Class, Expression, Function, If, Print, Return, Stmt, Var, While};
+use crate::span::Span;
+use crate::statement::VisitedElement as VisitedStmt;
+use crate::token::Token;
+
+/// A variable declared in a local scope.
+struct Local {
+    /// Whether the variable has finished being initialized.
+    defined: bool,
+    declared_at: Span,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+/// Static pass run between parsing and interpretation. It works out, for
+/// every variable reference, how many scopes away its declaration is and
+/// hands that distance to the interpreter. It also reports the errors that
+/// can be detected without running the program.
+pub struct Resolver<'a> {
+    interpreter: &'a mut Interpreter,
+    /// Local scopes only. Globals are not tracked.
+    scopes: Vec<HashMap<String, Local>>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(interpreter: &'a mut Interpreter) -> Self {
+        Resolver {
+            interpreter,
+            scopes: vec![],
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            diagnostics: vec![],
+        }
+    }
+
+    /// Errors found by `resolve`.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn resolve(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            statement.accept(self);
+        }
+    }
+
+    pub fn resolve_expression(&mut self, expression: &Expr) {
+        expression.accept(self);
+    }
+
+    fn resolve_function(&mut self, function: &Function, function_type: FunctionType) {
+        let enclosing_function = self.current_function;
+        self.current_function = function_type;
+
+        self.begin_scope();
+        for param in function.params.iter() {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve(&function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn resolve_local(&mut self, id: usize, name: &Token) {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(&name.lexeme) {
+                self.interpreter.resolve(id, depth);
+                return;
+            }
+        }
+        // Not found in any local scope: assume it is global.
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        if let Some(previous) = scope.get(&name.lexeme) {
+            let diagnostic = Diagnostic::error(RESOLUTION_ERROR, "Already a variable with this name in this scope.", name.span)
+                .with_label(previous.declared_at, "variable declared here")
+                .with_help("assign to the existing variable instead, or declare the new one in a nested block");
+            self.diagnostics.push(diagnostic);
+        }
+        scope.insert(name.lexeme.clone(), Local { defined: false, declared_at: name.span });
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.diagnostics.push(Diagnostic::error(RESOLUTION_ERROR, message, token.span));
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), Local { defined: true, declared_at: name.span });
+        }
+    }
+}
+
+impl expression::Visitor<()> for Resolver<'_> {
+    fn visit_assign(&mut self, element: &Assign) {
+        self.resolve_expression(&element.value);
+        self.resolve_local(element.id, &element.name);
+    }
+
+    fn visit_binary(&mut self, element: &Binary) {
+        self.resolve_expression(&element.left);
+        self.resolve_expression(&element.right);
+    }
+
+    fn visit_call(&mut self, element: &Call) {
+        self.resolve_expression(&element.callee);
+        for argument in element.arguments.iter() {
+            self.resolve_expression(argument);
+        }
+    }
+
+    fn visit_get(&mut self, element: &Get) {
+        self.resolve_expression(&element.object);
+    }
+
+    fn visit_grouping(&mut self, element: &Grouping) {
+        self.resolve_expression(&element.expression);
+    }
+
+    fn visit_literal(&mut self, _element: &Literal) {}
+
+    fn visit_logical(&mut self, element: &Logical) {
+        self.resolve_expression(&element.left);
+        self.resolve_expression(&element.right);
+    }
+
+    fn visit_set(&mut self, element: &Set) {
+        self.resolve_expression(&element.value);
+        self.resolve_expression(&element.object);
+    }
+
+    fn visit_super(&mut self, element: &Super) {
+        match self.current_class {
+            ClassType::None => self.error(&element.keyword, "Can't use 'super' outside of a class."),
+            ClassType::Class => self.error(&element.keyword, "Can't use 'super' in a class with no superclass."),
+            ClassType::Subclass => self.resolve_local(element.id, &element.keyword),
+        }
+    }
+
+    fn visit_this(&mut self, element: &This) {
+        if self.current_class == ClassType::None {
+            self.error(&element.keyword, "Can't use 'this' outside of a class.");
+            return;
+        }
+
+        self.resolve_local(element.id, &element.keyword);
+    }
+
+    fn visit_unary(&mut self, element: &Unary) {
+        self.resolve_expression(&element.right);
+    }
+
+    fn visit_variable(&mut self, element: &Variable) {
+        let declared_not_defined = self.scopes.last()
+            .and_then(|scope| scope.get(&element.name.lexeme))
+            .filter(|local| !local.defined)
+            .map(|local| local.declared_at);
+        if let Some(declared_at) = declared_not_defined {
+            let diagnostic = Diagnostic::error(RESOLUTION_ERROR, "Can't read local variable in its own initializer.", element.name.span)
+                .with_label(declared_at, "variable declared here");
+            self.diagnostics.push(diagnostic);
+        }
+
+        self.resolve_local(element.id, &element.name);
+    }
+}
+
+impl statement::Visitor<()> for Resolver<'_> {
+    fn visit_block(&mut self, element: &Block) {
+        self.begin_scope();
+        self.resolve(&element.statements);
+        self.end_scope();
+    }
+
+    fn visit_class(&mut self, element: &Class) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(&element.name);
+        self.define(&element.name);
+
+        if let Some(superclass) = &element.superclass {
+            if superclass.name.lexeme == element.name.lexeme {
+                self.error(&superclass.name, "A class can't inherit from itself.");
+            }
+
+            self.current_class = ClassType::Subclass;
+            superclass.accept(self);
+
+            // A subclass gets an extra scope binding `super` around its methods.
+            self.begin_scope();
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.insert("super".to_string(), Local { defined: true, declared_at: superclass.name.span });
+            }
+        }
+
+        // Methods are resolved inside a scope that binds `this`.
+        self.begin_scope();
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert("this".to_string(), Local { defined: true, declared_at: element.name.span });
+        }
+
+        for method in element.methods.iter() {
+            let function_type = if method.name.lexeme == "init" {
+                FunctionType::Initializer
+            } else {
+                FunctionType::Method
+            };
+            self.resolve_function(method, function_type);
+        }
+
+        self.end_scope();
+
+        if element.superclass.is_some() {
+            self.end_scope();
+        }
+
+        self.current_class = enclosing_class;
+    }
+
+    fn visit_expression(&mut self, element: &Expression) {
+        self.resolve_expression(&element.expression);
+    }
+
+    fn visit_function(&mut self, element: &Function) {
+        // Define eagerly so the function can refer to itself recursively.
+        self.declare(&element.name);
+        self.define(&element.name);
+
+        self.resolve_function(element, FunctionType::Function);
+    }
+
+    fn visit_if(&mut self, element: &If) {
+        self.resolve_expression(&element.condition);
+        element.then_branch.accept(self);
+        if let Some(else_branch) = &element.else_branch {
+            else_branch.accept(self);
+        }
+    }
+
+    fn visit_print(&mut self, element: &Print) {
+        self.resolve_expression(&element.expression);
+    }
+
+    fn visit_return(&mut self, element: &Return) {
+        if self.current_function == FunctionType::None {
+            self.error(&element.keyword, "Can't return from top-level code.");
+        }
+
+        if let Some(value) = &element.value {
+            if self.current_function == FunctionType::Initializer {
+                let diagnostic = Diagnostic::error(RESOLUTION_ERROR, "Can't return a value from an initializer.", element.keyword.span)
+                    .with_note("an initializer always returns the instance it was called on");
+                self.diagnostics.push(diagnostic);
+            }
+            self.resolve_expression(value);
+        }
+    }
+
+    fn visit_var(&mut self, element: &Var) {
+        self.declare(&element.name);
+        if let Some(initializer) = &element.initializer {
+            self.resolve_expression(initializer);
+        }
+        self.define(&element.name);
+    }
+
+    fn visit_while(&mut self, element: &While) {
+        self.resolve_expression(&element.condition);
+        element.body.accept(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse;
+
+    fn resolve(source: &str) -> (Vec<Stmt>, Interpreter, Vec<Diagnostic>) {
+        let statements = parse(source);
+        let mut interpreter = Interpreter::new();
+        let mut resolver = Resolver::new(&mut interpreter);
+        resolver.resolve(&statements);
+        let diagnostics = resolver.diagnostics().to_vec();
+        (statements, interpreter, diagnostics)
+    }
+
+    fn error_messages(source: &str) -> Vec<(usize, String)> {
+        let (_, _, diagnostics) = resolve(source);
+        diagnostics.into_iter().map(|diagnostic| (diagnostic.span.line, diagnostic.message)).collect()
+    }
+
+    /// The name and id of every variable read or assignment, in source
+    /// order. Only walks the kinds of nodes the tests below use.
+    fn references(statements: &[Stmt]) -> Vec<(String, usize)> {
+        fn walk_statement(statement: &Stmt, found: &mut Vec<(String, usize)>) {
+            match statement {
+                Stmt::Block(block) => block.statements.iter().for_each(|s| walk_statement(s, found)),
+                Stmt::Function(function) => function.body.iter().for_each(|s| walk_statement(s, found)),
+                Stmt::Expression(statement) => walk_expression(&statement.expression, found),
+                Stmt::Print(print) => walk_expression(&print.expression, found),
+                Stmt::Return(ret) => ret.value.iter().for_each(|value| walk_expression(value, found)),
+                Stmt::Var(var) => var.initializer.iter().for_each(|value| walk_expression(value, found)),
+                _ => panic!("unexpected statement in test program"),
+            }
+        }
+        fn walk_expression(expression: &Expr, found: &mut Vec<(String, usize)>) {
+            match expression {
+                Expr::Variable(variable) => found.push((variable.name.lexeme.clone(), variable.id)),
+                Expr::Assign(assign) => {
+                    walk_expression(&assign.value, found);
+                    found.push((assign.name.lexeme.clone(), assign.id));
+                }
+                Expr::Call(call) => walk_expression(&call.callee, found),
+                Expr::Binary(binary) => {
+                    walk_expression(&binary.left, found);
+                    walk_expression(&binary.right, found);
+                }
+                Expr::Literal(_) => {}
+                _ => panic!("unexpected expression in test program"),
+            }
+        }
+
+        let mut found = vec![];
+        statements.iter().for_each(|s| walk_statement(s, &mut found));
+        found
+    }
+
+    #[test]
+    fn records_scope_distances() {
+        let cases = [
+            ("var g = 1; print g;", vec![("g", None)]),
+            ("fun f(x) { return x; }", vec![("x", Some(0))]),
+            ("{ var a = 1; { a = a + 1; } }", vec![("a", Some(1)), ("a", Some(1))]),
+            ("{ var a = 1; fun f() { { print a; } } }", vec![("a", Some(2))]),
+            // The closure binds to the global `a`, even once the block
+            // declares its own after it.
+            (
+                "var a = \"global\"; { fun show() { print a; } show(); var a = \"local\"; show(); }",
+                vec![("a", None), ("show", Some(0)), ("show", Some(0))],
+            ),
+        ];
+        for (source, expected) in cases {
+            let (statements, interpreter, diagnostics) = resolve(source);
+            assert!(diagnostics.is_empty(), "unexpected diagnostics for {source:?}: {diagnostics:?}");
+            let depths: Vec<(String, Option<usize>)> = references(&statements).into_iter()
+                .map(|(name, id)| (name, interpreter.local_depth(id)))
+                .collect();
+            let expected: Vec<(String, Option<usize>)> = expected.into_iter()
+                .map(|(name, depth)| (name.to_string(), depth))
+                .collect();
+            assert_eq!(depths, expected, "resolving {source:?}");
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        let cases = [
+            ("{ var a = a; }", vec![(1, "Can't read local variable in its own initializer.")]),
+            ("{ var a = 1;\n  var a = 2; }", vec![(2, "Already a variable with this name in this scope.")]),
+            ("fun f(a, a) {}", vec![(1, "Already a variable with this name in this scope.")]),
+            ("return 1;", vec![(1, "Can't return from top-level code.")]),
+            ("print this;", vec![(1, "Can't use 'this' outside of a class.")]),
+            ("class A { init() { return 1; } }", vec![(1, "Can't return a value from an initializer.")]),
+            ("class A < A {}", vec![(1, "A class can't inherit from itself.")]),
+            ("print super.method;", vec![(1, "Can't use 'super' outside of a class.")]),
+            ("fun f() {\n  super.method();\n}", vec![(2, "Can't use 'super' outside of a class.")]),
+            ("class A {\n  m() { super.m(); }\n}", vec![(2, "Can't use 'super' in a class with no superclass.")]),
+            ("class A {}\nclass B < A { m() { super.m(); } }", vec![]),
+            // Globals may be redeclared, and read in their own initializer.
+            ("var a = 1; var a = a;", vec![]),
+            ("{ var a = 1; { var a = 2; } }", vec![]),
+        ];
+        for (source, expected) in cases {
+            let expected: Vec<(usize, String)> = expected.into_iter()
+                .map(|(line, message)| (line, message.to_string()))
+                .collect();
+            assert_eq!(error_messages(source), expected, "resolving {source:?}");
+        }
+    }
+
+    #[test]
+    fn points_redeclarations_at_the_declaration() {
+        let (_, _, diagnostics) = resolve("{\n  var a = 1;\n  var a = 2;\n}");
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!((diagnostic.span.line, diagnostic.span.column), (3, 7));
+        assert_eq!(diagnostic.labels.len(), 1);
+        let label = &diagnostic.labels[0];
+        assert_eq!((label.span.line, label.span.column, label.message.as_str()), (2, 7, "variable declared here"));
+        assert!(diagnostic.help.is_some());
+    }
+
+    #[test]
+    fn points_own_initializer_reads_at_the_declaration() {
+        let (_, _, diagnostics) = resolve("{ var a = a; }");
+        assert_eq!(diagnostics.len(), 1);
+        let label = &diagnostics[0].labels[0];
+        assert_eq!((label.span.column, label.message.as_str()), (7, "variable declared here"));
+    }
+}
@@ -0,0 +1,48 @@
+use crate::json::{Json, JsonError};
+
+/// Location of a piece of source code: the byte range it covers, and the
+/// line and column (both 1-based, column counted in chars) where it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// The span running from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end: other.end.max(self.end),
+            ..self
+        }
+    }
+
+    pub fn to_json(self) -> Json {
+        Json::object(vec![
+            ("start", Json::from(self.start)),
+            ("end", Json::from(self.end)),
+            ("line", Json::from(self.line)),
+            ("column", Json::from(self.column)),
+        ])
+    }
+
+    pub fn from_json(json: &Json) -> Result<Span, JsonError> {
+        Ok(Span::new(
+            json.field("start")?.as_usize()?,
+            json.field("end")?.as_usize()?,
+            json.field("line")?.as_usize()?,
+            json.field("column")?.as_usize()?,
+        ))
+    }
+}
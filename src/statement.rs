@@ -0,0 +1,272 @@
+use std::rc::Rc;
+use crate::expression::{Expr, Variable};
+use crate::span::Span;
+use crate::token::Token;
+#[derive(Clone)]
+pub enum Stmt {
+	Block(Block),
+	Class(Class),
+	Expression(Expression),
+	Function(Function),
+	If(If),
+	Print(Print),
+	Return(Return),
+	Var(Var),
+	While(While),
+}
+
+pub trait Visitor<R> {
+	fn visit_block(&mut self, element: &Block) -> R ;
+	fn visit_class(&mut self, element: &Class) -> R ;
+	fn visit_expression(&mut self, element: &Expression) -> R ;
+	fn visit_function(&mut self, element: &Function) -> R ;
+	fn visit_if(&mut self, element: &If) -> R ;
+	fn visit_print(&mut self, element: &Print) -> R ;
+	fn visit_return(&mut self, element: &Return) -> R ;
+	fn visit_var(&mut self, element: &Var) -> R ;
+	fn visit_while(&mut self, element: &While) -> R ;
+}
+
+pub trait VisitedElement {
+	fn accept<S: Visitor<R>, R>(&self, visitor: &mut S) -> R;
+}
+
+#[derive(Clone)]
+pub struct Block {
+	pub statements: Vec<Stmt>,
+	pub span: Span,
+}
+
+impl Block {
+	pub fn new(statements: Vec<Stmt>, span: Span, ) -> Self {
+		Block {
+			statements,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Block {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_block(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Class {
+	pub name: Token,
+	pub superclass: Option<Variable>,
+	pub methods: Vec<Function>,
+	pub span: Span,
+}
+
+impl Class {
+	pub fn new(name: Token, superclass: Option<Variable>, methods: Vec<Function>, span: Span, ) -> Self {
+		Class {
+			name,
+			superclass,
+			methods,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Class {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_class(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Expression {
+	pub expression: Box<Expr>,
+	pub span: Span,
+}
+
+impl Expression {
+	pub fn new(expression: Box<Expr>, span: Span, ) -> Self {
+		Expression {
+			expression,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Expression {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_expression(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Function {
+	pub name: Token,
+	pub params: Vec<Token>,
+	pub body: Rc<Vec<Stmt>>,
+	pub span: Span,
+}
+
+impl Function {
+	pub fn new(name: Token, params: Vec<Token>, body: Rc<Vec<Stmt>>, span: Span, ) -> Self {
+		Function {
+			name,
+			params,
+			body,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Function {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_function(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct If {
+	pub condition: Box<Expr>,
+	pub then_branch: Box<Stmt>,
+	pub else_branch: Option<Box<Stmt>>,
+	pub span: Span,
+}
+
+impl If {
+	pub fn new(condition: Box<Expr>, then_branch: Box<Stmt>, else_branch: Option<Box<Stmt>>, span: Span, ) -> Self {
+		If {
+			condition,
+			then_branch,
+			else_branch,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for If {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_if(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Print {
+	pub expression: Box<Expr>,
+	pub span: Span,
+}
+
+impl Print {
+	pub fn new(expression: Box<Expr>, span: Span, ) -> Self {
+		Print {
+			expression,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Print {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_print(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Return {
+	pub keyword: Token,
+	pub value: Option<Box<Expr>>,
+	pub span: Span,
+}
+
+impl Return {
+	pub fn new(keyword: Token, value: Option<Box<Expr>>, span: Span, ) -> Self {
+		Return {
+			keyword,
+			value,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Return {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_return(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct Var {
+	pub name: Token,
+	pub initializer: Option<Box<Expr>>,
+	pub span: Span,
+}
+
+impl Var {
+	pub fn new(name: Token, initializer: Option<Box<Expr>>, span: Span, ) -> Self {
+		Var {
+			name,
+			initializer,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for Var {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_var(self)
+	}
+}
+
+#[derive(Clone)]
+pub struct While {
+	pub condition: Box<Expr>,
+	pub body: Box<Stmt>,
+	pub span: Span,
+}
+
+impl While {
+	pub fn new(condition: Box<Expr>, body: Box<Stmt>, span: Span, ) -> Self {
+		While {
+			condition,
+			body,
+			span,
+		}
+	}
+}
+
+impl VisitedElement for While {
+	fn accept<V: Visitor<R>, R>(&self, visitor: &mut V) -> R {
+		visitor.visit_while(self)
+	}
+}
+
+impl VisitedElement for Stmt {
+	fn accept<S: Visitor<R>, R>(&self, visitor: &mut S) -> R {
+		match self {
+			 Stmt::Block(b) => {b.accept(visitor)},
+			 Stmt::Class(b) => {b.accept(visitor)},
+			 Stmt::Expression(b) => {b.accept(visitor)},
+			 Stmt::Function(b) => {b.accept(visitor)},
+			 Stmt::If(b) => {b.accept(visitor)},
+			 Stmt::Print(b) => {b.accept(visitor)},
+			 Stmt::Return(b) => {b.accept(visitor)},
+			 Stmt::Var(b) => {b.accept(visitor)},
+			 Stmt::While(b) => {b.accept(visitor)},
+		}
+	}
+}
+
+impl Stmt {
+	#[allow(dead_code)]
+	pub fn span(&self) -> Span {
+		match self {
+			 Stmt::Block(b) => {b.span},
+			 Stmt::Class(b) => {b.span},
+			 Stmt::Expression(b) => {b.span},
+			 Stmt::Function(b) => {b.span},
+			 Stmt::If(b) => {b.span},
+			 Stmt::Print(b) => {b.span},
+			 Stmt::Return(b) => {b.span},
+			 Stmt::Var(b) => {b.span},
+			 Stmt::While(b) => {b.span},
+		}
+	}
+}
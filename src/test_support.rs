@@ -0,0 +1,16 @@
+use crate::lox_parser::LoxParser;
+use crate::lox_scanner::LoxScanner;
+use crate::scanner::Scanner;
+use crate::statement::Stmt;
+use crate::token::Token;
+
+/// Scans a test program, errors and all.
+pub fn scan(source: &str) -> Vec<Token> {
+    LoxScanner::new(source.as_bytes()).scan_tokens()
+}
+
+/// Parses a test program, which is expected to be free of syntax errors.
+pub fn parse(source: &str) -> Vec<Stmt> {
+    LoxParser::new(scan(source)).parse()
+        .unwrap_or_else(|_| panic!("test program should parse: {source:?}"))
+}
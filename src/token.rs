@@ -1,12 +1,16 @@
+use crate::json::{Json, JsonError};
+use crate::span::Span;
 use crate::token_type::TokenType;
+use crate::value::LiteralValue;
 use std::fmt;
 
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
-    literal: String,
-    line: usize,
+    /// The value of a string or number literal, `None` for other tokens.
+    pub literal: Option<LiteralValue>,
+    pub span: Span,
 }
 
 
@@ -14,25 +18,90 @@ impl Token {
     pub fn new(
         token_type: TokenType,
         lexeme: String,
-        literal: String,
-        line: usize,) -> Self {
+        literal: Option<LiteralValue>,
+        span: Span,) -> Self {
         Token {
             token_type,
             lexeme,
             literal,
-            line
+            span,
         }
     }
 }
 
+impl Token {
+    /// The token as a JSON object, for the JSON lines token dump.
+    pub fn to_json(&self) -> Json {
+        let literal = match &self.literal {
+            Some(LiteralValue::Str(s)) => Json::from(s.as_str()),
+            Some(LiteralValue::Number(n)) => Json::Number(*n),
+            Some(LiteralValue::Bool(b)) => Json::Bool(*b),
+            Some(LiteralValue::Nil) | None => Json::Null,
+        };
+        Json::object(vec![
+            ("line", Json::from(self.span.line)),
+            ("column", Json::from(self.span.column)),
+            ("type", Json::String(self.token_type.to_string())),
+            ("lexeme", Json::from(self.lexeme.as_str())),
+            ("literal", literal),
+            ("start", Json::from(self.span.start)),
+            ("end", Json::from(self.span.end)),
+        ])
+    }
+
+    /// Rebuilds a token from the output of `to_json`.
+    pub fn from_json(json: &Json) -> Result<Token, JsonError> {
+        let name = json.field("type")?.as_str()?;
+        let (token_type, literal) = match (name, json.field("literal")?) {
+            ("STRING", literal) => {
+                let value = literal.as_str()?.to_string();
+                (TokenType::LoxString(value.clone()), Some(LiteralValue::Str(value)))
+            }
+            ("NUMBER", literal) => {
+                let value = literal.as_f64()?;
+                (TokenType::Number(value), Some(LiteralValue::Number(value)))
+            }
+            (name, _) => {
+                let token_type = TokenType::from_name(name)
+                    .ok_or_else(|| JsonError::new(&format!("Unknown token type '{name}'.")))?;
+                (token_type, None)
+            }
+        };
+        let span = Span::from_json(json)?;
+        Ok(Token::new(token_type, json.field("lexeme")?.as_str()?.to_string(), literal, span))
+    }
+}
+
+/// Prints the token on one line as `line:col TYPE 'lexeme' literal`, the
+/// literal only for strings and numbers. Control characters are escaped so
+/// that a token never spans several lines.
 impl fmt::Display for Token {
-    // This trait requires `fmt` with this exact signature.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Write strictly the first element into the supplied output
-        // stream: `f`. Returns `fmt::Result` which indicates whether the
-        // operation succeeded or failed. Note that `write!` uses syntax which
-        // is very similar to `println!`.
-        todo!()
-        // write!(f, "{}", self.0);
+        write!(f, "{}:{} {} '{}'", self.span.line, self.span.column, self.token_type, escape(&self.lexeme, '\''))?;
+        match &self.literal {
+            Some(LiteralValue::Str(s)) => write!(f, " \"{}\"", escape(s, '"')),
+            Some(literal) => write!(f, " {literal}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Escapes backslashes, control characters and the given quote.
+fn escape(text: &str, quote: char) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\\' => escaped.push_str("\\\\"),
+            c if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_control() => escaped.extend(c.escape_default()),
+            c => escaped.push(c),
+        }
     }
-}
\ No newline at end of file
+    escaped
+}
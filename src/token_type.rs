@@ -1,3 +1,6 @@
+use std::fmt;
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug,Clone, PartialEq)]
 pub enum TokenType {
     // Single character tokens
@@ -24,7 +27,7 @@ pub enum TokenType {
     // Literals
     Identifier,
     LoxString(String),
-    Number(f32),
+    Number(f64),
     // Keywords
     And,
     Class,
@@ -44,4 +47,71 @@ pub enum TokenType {
     While,
     // EOF
     EOF,
-}
\ No newline at end of file
+}
+/// Every token type without a payload, to look them up by name.
+const SIMPLE_TOKEN_TYPES: [TokenType; 37] = [
+    TokenType::LeftParen, TokenType::RightParen, TokenType::LeftBrace, TokenType::RightBrace,
+    TokenType::Comma, TokenType::Dot, TokenType::Minus, TokenType::Plus, TokenType::SemiColon,
+    TokenType::Slash, TokenType::Star, TokenType::Bang, TokenType::BangEqual, TokenType::Equal,
+    TokenType::EqualEqual, TokenType::Greater, TokenType::GreaterEqual, TokenType::Less,
+    TokenType::LessEqual, TokenType::Identifier, TokenType::And, TokenType::Class, TokenType::Else,
+    TokenType::False, TokenType::Fun, TokenType::For, TokenType::If, TokenType::Nil, TokenType::Or,
+    TokenType::Print, TokenType::Return, TokenType::Super, TokenType::This, TokenType::True,
+    TokenType::Var, TokenType::While, TokenType::EOF,
+];
+
+impl TokenType {
+    /// The token type printed as `name`, for types without a payload.
+    pub fn from_name(name: &str) -> Option<TokenType> {
+        SIMPLE_TOKEN_TYPES.into_iter().find(|token_type| token_type.to_string() == name)
+    }
+}
+
+/// The names used when printing tokens, in the style of the reference Lox
+/// implementation. Literal payloads are left out.
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            TokenType::LeftParen => "LEFT_PAREN",
+            TokenType::RightParen => "RIGHT_PAREN",
+            TokenType::LeftBrace => "LEFT_BRACE",
+            TokenType::RightBrace => "RIGHT_BRACE",
+            TokenType::Comma => "COMMA",
+            TokenType::Dot => "DOT",
+            TokenType::Minus => "MINUS",
+            TokenType::Plus => "PLUS",
+            TokenType::SemiColon => "SEMICOLON",
+            TokenType::Slash => "SLASH",
+            TokenType::Star => "STAR",
+            TokenType::Bang => "BANG",
+            TokenType::BangEqual => "BANG_EQUAL",
+            TokenType::Equal => "EQUAL",
+            TokenType::EqualEqual => "EQUAL_EQUAL",
+            TokenType::Greater => "GREATER",
+            TokenType::GreaterEqual => "GREATER_EQUAL",
+            TokenType::Less => "LESS",
+            TokenType::LessEqual => "LESS_EQUAL",
+            TokenType::Identifier => "IDENTIFIER",
+            TokenType::LoxString(_) => "STRING",
+            TokenType::Number(_) => "NUMBER",
+            TokenType::And => "AND",
+            TokenType::Class => "CLASS",
+            TokenType::Else => "ELSE",
+            TokenType::False => "FALSE",
+            TokenType::Fun => "FUN",
+            TokenType::For => "FOR",
+            TokenType::If => "IF",
+            TokenType::Nil => "NIL",
+            TokenType::Or => "OR",
+            TokenType::Print => "PRINT",
+            TokenType::Return => "RETURN",
+            TokenType::Super => "SUPER",
+            TokenType::This => "THIS",
+            TokenType::True => "TRUE",
+            TokenType::Var => "VAR",
+            TokenType::While => "WHILE",
+            TokenType::EOF => "EOF",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use crate::lox_callable::LoxCallable;
+use crate::lox_class::LoxClass;
+use crate::lox_instance::LoxInstance;
+
+/// A runtime Lox value, produced by evaluating an expression.
+#[derive(Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+    Callable(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+}
+
+impl Value {
+    /// `false` and `nil` are falsey, everything else is truthy.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Nil => false,
+            Value::Bool(b) => *b,
+            _ => true,
+        }
+    }
+}
+
+/// A constant written in the source: what a literal token carries and what
+/// a `Literal` expression evaluates to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiteralValue {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+}
+
+impl From<&LiteralValue> for Value {
+    fn from(literal: &LiteralValue) -> Self {
+        match literal {
+            LiteralValue::Nil => Value::Nil,
+            LiteralValue::Bool(b) => Value::Bool(*b),
+            LiteralValue::Number(n) => Value::Number(*n),
+            LiteralValue::Str(s) => Value::Str(s.clone()),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(l), Value::Bool(r)) => l == r,
+            (Value::Number(l), Value::Number(r)) => l == r,
+            (Value::Str(l), Value::Str(r)) => l == r,
+            // Objects are only equal to themselves.
+            (Value::Callable(l), Value::Callable(r)) => Rc::ptr_eq(l, r),
+            (Value::Class(l), Value::Class(r)) => Rc::ptr_eq(l, r),
+            (Value::Instance(l), Value::Instance(r)) => Rc::ptr_eq(l, r),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Number(n) => format_number(*n, f),
+            Value::Str(s) => write!(f, "{s}"),
+            Value::Callable(c) => write!(f, "{c}"),
+            Value::Class(c) => write!(f, "{c}"),
+            Value::Instance(i) => write!(f, "{}", i.borrow()),
+        }
+    }
+}
+
+/// Prints literals as they would be written in the source, so strings are
+/// quoted.
+impl fmt::Display for LiteralValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LiteralValue::Str(s) => write!(f, "\"{s}\""),
+            literal => write!(f, "{}", Value::from(literal)),
+        }
+    }
+}
+
+/// Prints numbers the way jlox does, which is Java's `Double.toString` with
+/// a trailing `.0` dropped: magnitudes from 10^-3 up to 10^7 in plain
+/// decimal, anything else in scientific notation such as `1.0E21`, and the
+/// IEEE special values spelled out.
+fn format_number(n: f64, f: &mut fmt::Formatter) -> fmt::Result {
+    if n.is_nan() {
+        write!(f, "NaN")
+    } else if n.is_infinite() {
+        write!(f, "{}Infinity", if n < 0.0 { "-" } else { "" })
+    } else if n == 0.0 || (1e-3..1e7).contains(&n.abs()) {
+        write!(f, "{n}")
+    } else {
+        // Rust and Java both print the shortest digits that round-trip,
+        // but Java always gives the mantissa a fraction.
+        let scientific = format!("{n:e}");
+        let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+        let fraction = if mantissa.contains('.') { "" } else { ".0" };
+        write!(f, "{mantissa}{fraction}E{exponent}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_numbers() {
+        let cases = [
+            (1.0, "1"),
+            (-0.5, "-0.5"),
+            (0.0, "0"),
+            (-0.0, "-0"),
+            (1234567.0, "1234567"),
+            (9999999.5, "9999999.5"),
+            (1e7, "1.0E7"),
+            (123456789012.0, "1.23456789012E11"),
+            (1e21, "1.0E21"),
+            (-1.5e30, "-1.5E30"),
+            (0.001, "0.001"),
+            (0.0001, "1.0E-4"),
+            (1e-7, "1.0E-7"),
+            (1.25e-10, "1.25E-10"),
+            (0.1 + 0.2, "0.30000000000000004"),
+            (f64::NAN, "NaN"),
+            (f64::NEG_INFINITY, "-Infinity"),
+        ];
+        for (n, text) in cases {
+            assert_eq!(Value::Number(n).to_string(), text, "formatting {n:?}");
+        }
+    }
+}